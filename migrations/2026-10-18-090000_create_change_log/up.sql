@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE change_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    change_set INTEGER NOT NULL,
+    operation VARCHAR(20) NOT NULL,
+    country_id INTEGER NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    status INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (country_id) REFERENCES countries(id)
+)
\ No newline at end of file
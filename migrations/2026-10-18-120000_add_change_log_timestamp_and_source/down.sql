@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE change_log DROP COLUMN changed_at;
+ALTER TABLE change_log DROP COLUMN source;
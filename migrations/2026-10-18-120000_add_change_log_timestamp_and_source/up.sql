@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE change_log_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    change_set INTEGER NOT NULL,
+    operation VARCHAR(20) NOT NULL,
+    country_id INTEGER NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    status INTEGER NOT NULL DEFAULT 0,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    source VARCHAR(20) NOT NULL DEFAULT 'unknown',
+    FOREIGN KEY (country_id) REFERENCES countries(id)
+);
+
+INSERT INTO change_log_new (id, change_set, operation, country_id, old_value, new_value, status)
+SELECT id, change_set, operation, country_id, old_value, new_value, status FROM change_log;
+
+DROP TABLE change_log;
+
+ALTER TABLE change_log_new RENAME TO change_log;
@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE change_log DROP COLUMN profile_id;
+
+ALTER TABLE country_notes DROP COLUMN profile_id;
+
+CREATE TABLE country_visits_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    country_id INTEGER NOT NULL UNIQUE,
+    FOREIGN KEY (country_id) REFERENCES countries(id)
+);
+
+INSERT OR IGNORE INTO country_visits_old (id, country_id)
+SELECT id, country_id FROM country_visits;
+
+DROP TABLE country_visits;
+
+ALTER TABLE country_visits_old RENAME TO country_visits;
+
+DROP TABLE profiles;
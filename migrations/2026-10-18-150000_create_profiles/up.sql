@@ -0,0 +1,27 @@
+-- Your SQL goes here
+CREATE TABLE profiles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name VARCHAR(100) NOT NULL UNIQUE
+);
+
+INSERT INTO profiles (id, name) VALUES (1, 'default');
+
+CREATE TABLE country_visits_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    profile_id INTEGER NOT NULL DEFAULT 1,
+    country_id INTEGER NOT NULL,
+    UNIQUE (profile_id, country_id),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id),
+    FOREIGN KEY (country_id) REFERENCES countries(id)
+);
+
+INSERT INTO country_visits_new (id, profile_id, country_id)
+SELECT id, 1, country_id FROM country_visits;
+
+DROP TABLE country_visits;
+
+ALTER TABLE country_visits_new RENAME TO country_visits;
+
+ALTER TABLE country_notes ADD profile_id INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE change_log ADD profile_id INTEGER NOT NULL DEFAULT 1;
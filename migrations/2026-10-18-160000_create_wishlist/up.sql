@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE wishlist_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    profile_id INTEGER NOT NULL,
+    country_id INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    priority INTEGER NOT NULL,
+    target_year INTEGER,
+    UNIQUE (profile_id, country_id),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id),
+    FOREIGN KEY (country_id) REFERENCES countries(id)
+);
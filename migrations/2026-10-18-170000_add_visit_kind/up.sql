@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE country_visits ADD kind VARCHAR(20) NOT NULL DEFAULT 'visited';
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE country_visits ADD visited_on DATE;
+UPDATE country_visits SET visited_on = (
+    SELECT date(max(change_log.changed_at)) FROM change_log
+    WHERE change_log.profile_id = country_visits.profile_id
+      AND change_log.country_id = country_visits.country_id
+      AND change_log.new_value = 'true'
+      AND change_log.status IN (0, 3)
+);
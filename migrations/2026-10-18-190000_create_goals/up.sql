@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE goals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    profile_id INTEGER NOT NULL,
+    kind VARCHAR(20) NOT NULL,
+    count INTEGER,
+    year INTEGER,
+    continent VARCHAR(20),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id)
+);
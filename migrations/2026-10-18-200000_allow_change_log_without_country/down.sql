@@ -0,0 +1,22 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE change_log_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    change_set INTEGER NOT NULL,
+    operation VARCHAR(20) NOT NULL,
+    country_id INTEGER NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    status INTEGER NOT NULL DEFAULT 0,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    source VARCHAR(20) NOT NULL DEFAULT 'unknown',
+    profile_id INTEGER NOT NULL DEFAULT 1,
+    FOREIGN KEY (country_id) REFERENCES countries(id)
+);
+
+INSERT INTO change_log_old (id, change_set, operation, country_id, old_value, new_value, status, changed_at, source, profile_id)
+SELECT id, change_set, operation, country_id, old_value, new_value, status, changed_at, source, profile_id FROM change_log
+WHERE country_id IS NOT NULL;
+
+DROP TABLE change_log;
+
+ALTER TABLE change_log_old RENAME TO change_log;
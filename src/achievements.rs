@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use crate::base_data::{Continent, Grouping};
+use crate::state::AppState;
+
+lazy_static! {
+    pub static ref ACHIEVEMENTS: Vec<Achievement> = serde_yaml::from_str(include_str!("achievements.yaml")).unwrap();
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(with = "serde_yaml::with::singleton_map")]
+    pub rule: Rule,
+}
+
+/// What has to be visited to unlock an achievement.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// At least this many countries.
+    Countries(usize),
+    /// Countries on at least this many continents.
+    Continents(usize),
+    /// Every member of the grouping with this id.
+    AllOf(String),
+}
+
+impl Achievement {
+    /// How far the visited countries go towards the achievement, as reached and needed count.
+    pub fn progress(&self, visited: &HashSet<&str>) -> (usize, usize) {
+        match &self.rule {
+            Rule::Countries(count) => (visited.len().min(*count), *count),
+            Rule::Continents(count) => {
+                let continents: HashSet<Continent> = visited.iter()
+                    .filter_map(|iso2| Continent::of_iso2(iso2))
+                    .collect();
+                (continents.len().min(*count), *count)
+            }
+            Rule::AllOf(grouping) => {
+                let members = Grouping::by_id(grouping).map(|grouping| grouping.members.as_slice()).unwrap_or_default();
+                let reached = members.iter().filter(|member| visited.contains(member.as_str())).count();
+                (reached, members.len())
+            }
+        }
+    }
+
+    /// What the progress counts, to be shown after it.
+    pub fn counted(&self) -> String {
+        match &self.rule {
+            Rule::Countries(_) => "countries".to_string(),
+            Rule::Continents(_) => "continents".to_string(),
+            Rule::AllOf(grouping) => Grouping::by_id(grouping)
+                .map(|grouping| grouping.name.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn is_reached(&self, visited: &HashSet<&str>) -> bool {
+        let (reached, needed) = self.progress(visited);
+        needed > 0 && reached >= needed
+    }
+}
+
+/// Achievements the visits unlock, each with the day of the visit that unlocked it. Visits
+/// without a date count as the earliest, so achievements they unlock have no date either.
+pub fn unlocked_by(visits: &[(&str, Option<NaiveDate>)]) -> Vec<(&'static Achievement, Option<NaiveDate>)> {
+    let mut visits = visits.to_vec();
+    visits.sort_by_key(|(_, date)| *date);
+    ACHIEVEMENTS.iter()
+        .filter_map(|achievement| {
+            let mut visited = HashSet::new();
+            visits.iter()
+                .find(|(iso2, _)| {
+                    visited.insert(*iso2);
+                    achievement.is_reached(&visited)
+                })
+                .map(|(_, date)| (achievement, *date))
+        })
+        .collect()
+}
+
+pub fn unlocked(state: &AppState) -> Vec<(&'static Achievement, Option<NaiveDate>)> {
+    let visits: Vec<(&str, Option<NaiveDate>)> = state.countries()
+        .filter(|country| state.is_visited(country))
+        .map(|country| (country.iso2.as_str(), state.visit_date(country)))
+        .collect();
+    unlocked_by(&visits)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use crate::achievements::{ACHIEVEMENTS, Rule, unlocked_by};
+    use crate::base_data::Grouping;
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn rules_refer_to_known_groupings() {
+        for achievement in ACHIEVEMENTS.iter() {
+            if let Rule::AllOf(grouping) = &achievement.rule {
+                assert!(Grouping::by_id(grouping).is_some(), "{}", achievement.id);
+            }
+        }
+    }
+
+    #[test]
+    fn achievements_are_dated_by_the_visit_completing_them() {
+        let visits = [
+            ("SE", date(2015, 7, 1)),
+            ("NO", None),
+            ("IS", date(2022, 3, 4)),
+            ("DK", date(2012, 1, 1)),
+            ("FI", date(2019, 8, 9)),
+        ];
+        let unlocked = unlocked_by(&visits);
+        let date_of = |id: &str| unlocked.iter().find(|(achievement, _)| achievement.id == id).map(|(_, date)| *date);
+        assert_eq!(Some(date(2022, 3, 4)), date_of("nordic"));
+        assert_eq!(Some(None), date_of("first_country"));
+        assert_eq!(None, date_of("ten_countries"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{BTreeSet, HashMap};
+use lazy_static::lazy_static;
+use crate::svg_helper::{COUNTRY_POLYGONS, Polygon, SVG_HEIGT, SVG_WIDTH};
+
+/// Border vertices of two countries closer than this, in SVG units, are considered the same.
+const VERTEX_TOLERANCE: f32 = 0.5;
+/// Number of shared vertices needed to count as a shared border rather than a touching corner.
+const SHARED_VERTICES: usize = 2;
+
+lazy_static! {
+    /// ISO2 codes of the countries sharing a land border, derived from the border polygons.
+    pub static ref NEIGHBOURS: HashMap<String, BTreeSet<String>> = adjacency_graph(&COUNTRY_POLYGONS);
+    /// Colour index of every country, chosen so that neighbours never share one.
+    pub static ref GRAPH_COLORS: HashMap<String, usize> = color_graph(&NEIGHBOURS);
+}
+
+pub fn neighbours(iso2: &str) -> impl Iterator<Item = &'static str> {
+    NEIGHBOURS.get(iso2)
+        .into_iter()
+        .flat_map(|neighbours| neighbours.iter().map(String::as_str))
+}
+
+pub fn graph_color(iso2: &str) -> usize {
+    GRAPH_COLORS.get(iso2).copied().unwrap_or_default()
+}
+
+type Cell = (i32, i32);
+
+fn adjacency_graph(polygons: &HashMap<String, Vec<Polygon>>) -> HashMap<String, BTreeSet<String>> {
+    let cell_of = |x: f32, y: f32| -> Cell {
+        ((x * SVG_WIDTH / VERTEX_TOLERANCE).round() as i32, (y * SVG_HEIGT / VERTEX_TOLERANCE).round() as i32)
+    };
+    let mut countries_by_cell: HashMap<Cell, BTreeSet<&str>> = HashMap::new();
+    for (iso2, country_polygons) in polygons {
+        for point in country_polygons.iter().flat_map(Polygon::iter) {
+            countries_by_cell.entry(cell_of(point.0, point.1)).or_default().insert(iso2);
+        }
+    }
+    let mut shared_vertices: HashMap<(&str, &str), usize> = HashMap::new();
+    for (iso2, country_polygons) in polygons {
+        for point in country_polygons.iter().flat_map(Polygon::iter) {
+            let (x, y) = cell_of(point.0, point.1);
+            let nearby: BTreeSet<&str> = (-1..=1)
+                .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+                .filter_map(|cell| countries_by_cell.get(&cell))
+                .flatten()
+                .copied()
+                .filter(|other| *other != iso2)
+                .collect();
+            for other in nearby {
+                *shared_vertices.entry((iso2, other)).or_default() += 1;
+            }
+        }
+    }
+    let mut graph: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for ((a, b), count) in shared_vertices {
+        if count >= SHARED_VERTICES {
+            graph.entry(a.to_string()).or_default().insert(b.to_string());
+            graph.entry(b.to_string()).or_default().insert(a.to_string());
+        }
+    }
+    graph
+}
+
+/// Greedy colouring that handles the countries with the most neighbours first, which keeps the
+/// number of colours low for map-like graphs.
+fn color_graph(graph: &HashMap<String, BTreeSet<String>>) -> HashMap<String, usize> {
+    let mut countries: Vec<&String> = graph.keys().collect();
+    countries.sort_by(|a, b| graph[*b].len().cmp(&graph[*a].len()).then_with(|| a.cmp(b)));
+    let mut colors: HashMap<String, usize> = HashMap::new();
+    for iso2 in countries {
+        let taken: BTreeSet<usize> = graph[iso2].iter()
+            .filter_map(|neighbour| colors.get(neighbour))
+            .copied()
+            .collect();
+        let color = (0..).find(|color| !taken.contains(color)).unwrap_or_default();
+        colors.insert(iso2.clone(), color);
+    }
+    colors
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adjacency::{GRAPH_COLORS, graph_color, neighbours, NEIGHBOURS};
+
+    #[test]
+    fn finds_land_borders() {
+        let germany: Vec<&str> = neighbours("DE").collect();
+        assert_eq!(vec!["AT", "BE", "CH", "CZ", "DK", "FR", "LU", "NL", "PL"], germany);
+        assert_eq!(vec!["CA", "MX"], neighbours("US").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn islands_have_no_neighbours() {
+        assert_eq!(0, neighbours("IS").count());
+        assert_eq!(0, neighbours("JP").count());
+    }
+
+    #[test]
+    fn neighbours_never_share_a_color() {
+        for (iso2, neighbours) in NEIGHBOURS.iter() {
+            for neighbour in neighbours {
+                assert_ne!(graph_color(iso2), graph_color(neighbour), "{} and {}", iso2, neighbour);
+            }
+        }
+        assert!(GRAPH_COLORS.values().all(|color| *color < 6));
+    }
+}
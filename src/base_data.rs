@@ -1,18 +1,106 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use lazy_static::lazy_static;
 use serde::Deserialize;
+use crate::search;
 
 lazy_static! {
     pub static ref COUNTRIES: HashMap<String, BaseDataCountry> = {
         let vec: Vec<BaseDataCountry> = serde_yaml::from_str(include_str!("countries.yaml")).unwrap();
         vec.into_iter().map(|country| (country.name.to_lowercase(), country)).collect()
     };
+    static ref COUNTRIES_BY_ISO2: HashMap<String, &'static BaseDataCountry> = {
+        COUNTRIES.values().map(|country| (country.iso_code.alpha2.clone(), country)).collect()
+    };
+    pub static ref GROUPINGS: Vec<Grouping> = serde_yaml::from_str(include_str!("groupings.yaml")).unwrap();
 }
 
 #[derive(Deserialize, Clone)]
 pub struct BaseDataCountry {
     pub name: String,
     pub iso_code: ISOCode,
+    pub continent: Continent,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// `None` for territories nobody lives in permanently.
+    #[serde(default)]
+    pub capital: Option<String>,
+    pub population: u64,
+    /// Area in km².
+    pub area: f64,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Continent {
+    Africa,
+    Antarctica,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    pub const ALL: [Continent; 7] = [
+        Continent::Africa,
+        Continent::Antarctica,
+        Continent::Asia,
+        Continent::Europe,
+        Continent::NorthAmerica,
+        Continent::Oceania,
+        Continent::SouthAmerica,
+    ];
+
+    pub fn of_iso2(iso2: &str) -> Option<Continent> {
+        BaseDataCountry::by_iso2(iso2).map(|country| country.continent)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Continent::Africa => "africa",
+            Continent::Antarctica => "antarctica",
+            Continent::Asia => "asia",
+            Continent::Europe => "europe",
+            Continent::NorthAmerica => "north_america",
+            Continent::Oceania => "oceania",
+            Continent::SouthAmerica => "south_america",
+        }
+    }
+
+    pub fn parse(continent: &str) -> Option<Self> {
+        Continent::ALL.into_iter().find(|candidate| candidate.as_str() == continent)
+    }
+}
+
+impl Display for Continent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Continent::Africa => write!(f, "Africa"),
+            Continent::Antarctica => write!(f, "Antarctica"),
+            Continent::Asia => write!(f, "Asia"),
+            Continent::Europe => write!(f, "Europe"),
+            Continent::NorthAmerica => write!(f, "North America"),
+            Continent::Oceania => write!(f, "Oceania"),
+            Continent::SouthAmerica => write!(f, "South America"),
+        }
+    }
+}
+
+/// Political or economic grouping of countries, like the European Union.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Grouping {
+    pub id: String,
+    pub name: String,
+    /// ISO2 codes of the member countries.
+    pub members: Vec<String>,
+}
+
+impl Grouping {
+    pub fn by_id(id: &str) -> Option<&'static Grouping> {
+        GROUPINGS.iter().find(|grouping| grouping.id == id)
+    }
 }
 
 #[derive(Deserialize, Clone)]
@@ -26,10 +114,80 @@ impl BaseDataCountry {
         COUNTRIES.get(&name.to_lowercase())
     }
 
+    pub fn by_iso2(iso2: &str) -> Option<&'static BaseDataCountry> {
+        COUNTRIES_BY_ISO2.get(iso2).copied()
+    }
+
     pub fn matches_filter(&self, filter: &str) -> bool {
-        let filter = filter.to_lowercase();
-        self.name.to_lowercase().contains(&filter)
-            || self.iso_code.alpha2.to_lowercase().contains(&filter)
-            || self.iso_code.alpha3.to_lowercase().contains(&filter)
+        self.search_score(filter).is_some()
+    }
+
+    pub fn search_score(&self, filter: &str) -> Option<u32> {
+        search::country_score(filter, &self.name, &self.iso_code.alpha2, &self.iso_code.alpha3)
+    }
+
+    pub fn population_text(&self) -> String {
+        with_thousands_separators(self.population)
+    }
+
+    pub fn area_text(&self) -> String {
+        if self.area < 10.0 {
+            format!("{:.2} km²", self.area)
+        } else {
+            format!("{} km²", with_thousands_separators(self.area.round() as u64))
+        }
+    }
+}
+
+fn with_thousands_separators(number: u64) -> String {
+    let digits = number.to_string();
+    let mut text = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            text.push(',');
+        }
+        text.push(digit);
     }
+    text
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::base_data::{BaseDataCountry, Continent, GROUPINGS};
+
+    #[test]
+    fn countries_are_on_their_continent() {
+        assert_eq!(Some(Continent::Europe), Continent::of_iso2("DE"));
+        assert_eq!(Some(Continent::SouthAmerica), Continent::of_iso2("BR"));
+        assert_eq!(Some(Continent::Oceania), Continent::of_iso2("NZ"));
+        assert_eq!(None, Continent::of_iso2("XX"));
+    }
+
+    #[test]
+    fn facts_are_formatted_for_display() {
+        let germany = BaseDataCountry::by_iso2("DE").unwrap();
+        assert_eq!(Some("Berlin"), germany.capital.as_deref());
+        assert_eq!("357,588 km²", germany.area_text());
+        assert_eq!("0.49 km²", BaseDataCountry::by_iso2("VA").unwrap().area_text());
+        assert_eq!("47", BaseDataCountry::by_iso2("PN").unwrap().population_text());
+        assert_eq!(None, BaseDataCountry::by_iso2("AQ").unwrap().capital);
+    }
+
+    #[test]
+    fn grouping_members_are_known_countries() {
+        for grouping in GROUPINGS.iter() {
+            for member in &grouping.members {
+                assert!(BaseDataCountry::by_iso2(member).is_some(), "{} in {}", member, grouping.id);
+            }
+        }
+    }
+
+    #[test]
+    fn aliases_match_exactly() {
+        for (iso2, alias) in [("NL", "Holland"), ("GB", "UK"), ("MM", "Burma"), ("CI", "Ivory Coast")] {
+            let country = BaseDataCountry::by_iso2(iso2).unwrap();
+            assert_eq!(Some(0), country.search_score(alias));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use clap::Subcommand;
+use crate::database;
+use crate::geocode;
+use crate::database::connection;
+use crate::error::{Context, Error, ErrorKind};
+use crate::compare::{compare, TravelLog};
+use crate::color_scheme::MapColors;
+use crate::importer::{export_change_log, read_travel_log};
+use crate::models::{ChangeSource, Profile};
+use crate::replay::{export, frame_dates, render_frames, ReplayFormat};
+use crate::report::write_report;
+use crate::photos::read_photo_points;
+use crate::state::AppState;
+use crate::visit_proposals;
+use crate::visit_proposals::{GpsPoint, read_gpx_files};
+
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// Undo the last change to the visited countries
+    Undo,
+    /// Redo the last undone change
+    Redo,
+    /// Export every recorded change with its time and source as JSON
+    ExportChangeLog {
+        path: String,
+    },
+    /// Compare the visited countries of all profiles
+    Profiles {
+        /// Count countries that were only passed through in transit
+        #[arg(long)]
+        include_transit: bool,
+    },
+    /// Compare two travel logs, given as export files or, with --profiles, as profile names
+    Compare {
+        first: String,
+        second: String,
+        #[arg(long)]
+        profiles: bool,
+    },
+    /// Render how the map filled in over time, one frame per day with visits
+    Timeline {
+        path: String,
+        #[arg(long, value_enum, default_value_t = ReplayFormat::Gif)]
+        format: ReplayFormat,
+        /// Width of the frames in pixels
+        #[arg(long, default_value_t = 1000)]
+        width: u32,
+        /// How long each frame of the GIF is shown, in milliseconds
+        #[arg(long, default_value_t = 400)]
+        frame_ms: u32,
+        #[arg(long)]
+        color_blind_safe: bool,
+    },
+    /// Propose the countries crossed by the tracks and waypoints of GPX files and mark them as
+    /// visited once confirmed
+    ImportGpx {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Mark the countries without asking
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Mark the countries geotagged JPEG and TIFF photos in a directory were taken in as visited
+    ImportPhotos {
+        directory: PathBuf,
+        /// Only show the countries found
+        #[arg(long, conflicts_with = "yes")]
+        dry_run: bool,
+        /// Mark the countries without asking
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Show the country at a point given as latitude and longitude in degrees
+    Locate {
+        #[arg(allow_negative_numbers = true)]
+        latitude: f64,
+        #[arg(allow_negative_numbers = true)]
+        longitude: f64,
+    },
+    /// Show the goals of the profile and how far along they are
+    Goals,
+    /// Write a travel report with map, visited countries, notes and totals as a single HTML file
+    Report {
+        path: String,
+        /// Only countries visited in this year, visits without a date count in the year they
+        /// were logged
+        #[arg(long)]
+        year: Option<i32>,
+        #[arg(long)]
+        color_blind_safe: bool,
+    },
+}
+
+pub fn run(command: &CliCommand, profile: &Profile) -> Result<(), Error> {
+    match command {
+        CliCommand::Undo => step_history(profile, true),
+        CliCommand::Redo => step_history(profile, false),
+        CliCommand::ExportChangeLog { path } => {
+            export_change_log(Path::new(path), profile).context(format!("Cannot export change log to {}", path))
+        }
+        CliCommand::Profiles { include_transit } => compare_profiles(*include_transit),
+        CliCommand::Compare { first, second, profiles } => {
+            let (first, second) = if *profiles {
+                (profile_travel_log(first)?, profile_travel_log(second)?)
+            } else {
+                (file_travel_log(first)?, file_travel_log(second)?)
+            };
+            compare_travel_logs(&first, &second)
+        }
+        CliCommand::Timeline { path, format, width, frame_ms, color_blind_safe } => {
+            let map_colors = MapColors { color_blind_safe: *color_blind_safe, ..MapColors::default() };
+            export_timeline(profile, Path::new(path), *format, *width, *frame_ms, map_colors)
+        }
+        CliCommand::ImportGpx { paths, yes } => {
+            let points = read_gpx_files(paths).context("Cannot read GPX files")?;
+            import_points(profile, &points, *yes, false)
+        }
+        CliCommand::ImportPhotos { directory, dry_run, yes } => {
+            let points = read_photo_points(directory).context(format!("Cannot read photos in {}", directory.display()))?;
+            import_points(profile, &points, *yes, *dry_run)
+        }
+        CliCommand::Locate { latitude, longitude } => locate(*latitude, *longitude),
+        CliCommand::Goals => print_goals(profile),
+        CliCommand::Report { path, year, color_blind_safe } => {
+            let map_colors = MapColors { color_blind_safe: *color_blind_safe, ..MapColors::default() };
+            write_report(Path::new(path), profile, *year, map_colors).context(format!("Cannot write report to {}", path))?;
+            println!("Wrote report to {}", path);
+            Ok(())
+        }
+    }
+}
+
+fn step_history(profile: &Profile, undo: bool) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let (entries, done, nothing_done) = if undo {
+        (database::undo(&mut connection, profile.id, ChangeSource::Cli).context("Cannot undo")?, "Undone", "Nothing to undo")
+    } else {
+        (database::redo(&mut connection, profile.id, ChangeSource::Cli).context("Cannot redo")?, "Redone", "Nothing to redo")
+    };
+    if entries.is_empty() {
+        println!("{}", nothing_done);
+    }
+    for entry in entries {
+        let country = match entry.country_id {
+            Some(country_id) => database::country_by_id(&mut connection, country_id).context("Cannot load country")?,
+            None => None,
+        };
+        let country_name = country.map(|country| country.name).unwrap_or_default();
+        println!("{} change set {}: {} {}", done, entry.change_set, entry.operation, country_name);
+    }
+    Ok(())
+}
+
+fn export_timeline(profile: &Profile, path: &Path, format: ReplayFormat, width: u32, frame_ms: u32, map_colors: MapColors) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let state = AppState::load(&mut connection, profile.clone()).context("Cannot load visits")?;
+    let dates = frame_dates(state.visit_dates(), Local::now().date_naive());
+    let frames = render_frames(&state, &dates, width, map_colors).context("Cannot draw the map")?;
+    let written = export(frames, path, format, frame_ms).context(format!("Cannot write {}", path.display()))?;
+    println!("Wrote {} frames to {}", dates.len(), written.display());
+    Ok(())
+}
+
+/// Lists the countries the points lie in and marks them as visited if confirmed.
+fn import_points(profile: &Profile, points: &[GpsPoint], yes: bool, dry_run: bool) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let proposals = visit_proposals::propose(&mut connection, points).context("Cannot load countries")?;
+    if proposals.is_empty() {
+        println!("No countries found in {} points", points.len());
+        return Ok(());
+    }
+    let visited: HashSet<i32> = database::visited_countries(&mut connection, profile.id).context("Cannot load visits")?
+        .into_iter()
+        .map(|country| country.id)
+        .collect();
+    for proposal in &proposals {
+        let dates = match (proposal.first, proposal.last) {
+            (Some(first), Some(last)) if first == last => first.to_string(),
+            (Some(first), Some(last)) => format!("{} to {}", first, last),
+            _ => "no date".to_string(),
+        };
+        let status = if visited.contains(&proposal.country.id) { "visited before" } else { "new" };
+        println!("{} {:<30} {:<24} {:>7} points  {}", proposal.country.iso2, proposal.country.name, dates, proposal.points, status);
+    }
+    if dry_run {
+        return Ok(());
+    }
+    if !yes && !confirm(&format!("Mark these {} countries as visited?", proposals.len())) {
+        println!("Nothing imported");
+        return Ok(());
+    }
+    visit_proposals::accept(&mut connection, profile.id, &proposals).context("Cannot mark countries as visited")?;
+    println!("Marked {} countries as visited", proposals.len());
+    Ok(())
+}
+
+fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+fn locate(latitude: f64, longitude: f64) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    match geocode::country_at(&mut connection, latitude, longitude).context("Cannot load country")? {
+        Some(country) => println!("{} {}", country.iso2, country.name),
+        None => println!("No country at {}, {}", latitude, longitude),
+    }
+    Ok(())
+}
+
+fn print_goals(profile: &Profile) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let state = AppState::load(&mut connection, profile.clone()).context("Cannot load visits")?;
+    if state.goals().is_empty() {
+        println!("No goals set");
+    }
+    for (_, goal) in state.goals() {
+        let (reached, needed) = state.goal_progress(goal);
+        let done = if needed > 0 && reached >= needed { "done" } else { "" };
+        println!("{:<40} {:>4} of {:<4} {}", goal.to_string(), reached, needed, done);
+    }
+    Ok(())
+}
+
+fn compare_profiles(include_transit: bool) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let visited = database::visited_country_ids_by_profile(&mut connection, include_transit).context("Cannot load visits")?;
+    let visited_by_any: HashSet<i32> = visited.iter()
+        .flat_map(|(_, ids)| ids.iter().copied())
+        .collect();
+    let visited_by_all: HashSet<i32> = visited_by_any.iter()
+        .copied()
+        .filter(|id| visited.iter().all(|(_, ids)| ids.contains(id)))
+        .collect();
+    println!("{:<20} {:>8} {:>8}", "Profile", "Visited", "Only");
+    for (profile, ids) in &visited {
+        let only_this_profile = ids.iter()
+            .filter(|id| visited.iter().all(|(other, other_ids)| other.id == profile.id || !other_ids.contains(id)))
+            .count();
+        println!("{:<20} {:>8} {:>8}", profile.name, ids.len(), only_this_profile);
+    }
+    println!("Visited by any profile: {}", visited_by_any.len());
+    println!("Visited by all profiles: {}", visited_by_all.len());
+    Ok(())
+}
+
+fn profile_travel_log(profile_name: &str) -> Result<TravelLog, Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let profile = database::profile_by_name(&mut connection, profile_name)
+        .context(format!("Cannot load profile {}", profile_name))?
+        .ok_or(ErrorKind::UnknownProfile(profile_name.to_string()))
+        .context("Cannot compare profiles")?;
+    let visited = database::visited_countries(&mut connection, profile.id).context("Cannot load visits")?;
+    Ok(TravelLog::new(profile.name, visited))
+}
+
+fn file_travel_log(path: &str) -> Result<TravelLog, Error> {
+    read_travel_log(Path::new(path)).context(format!("Cannot read {}", path))
+}
+
+fn compare_travel_logs(first: &TravelLog, second: &TravelLog) -> Result<(), Error> {
+    let mut connection = connection().context("Cannot open database")?;
+    let countries = database::all_countries(&mut connection).context("Cannot load countries")?;
+    let comparison = compare(&countries, |country| first.has_visited(country), |country| second.has_visited(country));
+    for (side, countries) in comparison {
+        println!("{} ({}):", side.title(&first.name, &second.name), countries.len());
+        for country in countries {
+            println!("  {} {}", country.iso2, country.name);
+        }
+    }
+    Ok(())
+}
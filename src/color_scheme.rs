@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+use iced::Color;
+use serde::{Deserialize, Serialize};
+use crate::base_data::Continent;
+use crate::models::WishlistStatus;
+
+/// How the countries on the map are coloured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorScheme {
+    /// Every country gets its own colour derived from its ISO3 code.
+    #[default]
+    Classic,
+    Visited,
+    Continent,
+    /// Choropleth of how recently the country was visited, by the date of the visit.
+    Recency,
+    /// Neighbouring countries never share a colour.
+    Neighbours,
+}
+
+impl ColorScheme {
+    pub const ALL: [ColorScheme; 5] = [
+        ColorScheme::Classic,
+        ColorScheme::Visited,
+        ColorScheme::Continent,
+        ColorScheme::Recency,
+        ColorScheme::Neighbours,
+    ];
+}
+
+impl Display for ColorScheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColorScheme::Classic => write!(f, "classic"),
+            ColorScheme::Visited => write!(f, "visited / not visited"),
+            ColorScheme::Continent => write!(f, "by continent"),
+            ColorScheme::Recency => write!(f, "by recency"),
+            ColorScheme::Neighbours => write!(f, "distinct neighbours"),
+        }
+    }
+}
+
+/// The colour scheme together with the palette it is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MapColors {
+    pub scheme: ColorScheme,
+    /// Uses the Okabe-Ito and viridis palettes, which stay distinguishable with the common
+    /// forms of colour blindness.
+    pub color_blind_safe: bool,
+}
+
+impl MapColors {
+    pub fn visited(&self) -> Color {
+        if self.color_blind_safe {
+            rgb(0x00, 0x72, 0xB2)
+        } else {
+            Color::from_rgb(0.2, 0.65, 0.3)
+        }
+    }
+
+    pub fn wish(&self, status: WishlistStatus) -> Color {
+        match (status, self.color_blind_safe) {
+            (WishlistStatus::Planned, false) => Color::from_rgb(0.95, 0.75, 0.1),
+            (WishlistStatus::Wishlist, false) => Color::from_rgb(1.0, 0.9, 0.5),
+            (WishlistStatus::Planned, true) => rgb(0xE6, 0x9F, 0x00),
+            (WishlistStatus::Wishlist, true) => rgb(0xF0, 0xE4, 0x42),
+        }
+    }
+
+    pub fn continent(&self, continent: Continent) -> Color {
+        if self.color_blind_safe {
+            match continent {
+                Continent::Africa => rgb(0xE6, 0x9F, 0x00),
+                Continent::Antarctica => rgb(0x99, 0x99, 0x99),
+                Continent::Asia => rgb(0xD5, 0x5E, 0x00),
+                Continent::Europe => rgb(0x00, 0x72, 0xB2),
+                Continent::NorthAmerica => rgb(0x56, 0xB4, 0xE9),
+                Continent::Oceania => rgb(0xCC, 0x79, 0xA7),
+                Continent::SouthAmerica => rgb(0x00, 0x9E, 0x73),
+            }
+        } else {
+            match continent {
+                Continent::Africa => rgb(0xD9, 0x8C, 0x2B),
+                Continent::Antarctica => rgb(0xB0, 0xC4, 0xDE),
+                Continent::Asia => rgb(0xC6, 0x3D, 0x3D),
+                Continent::Europe => rgb(0x3D, 0x6F, 0xC6),
+                Continent::NorthAmerica => rgb(0x8E, 0x5C, 0xC2),
+                Continent::Oceania => rgb(0x2B, 0xA8, 0xA0),
+                Continent::SouthAmerica => rgb(0x4C, 0xA8, 0x3A),
+            }
+        }
+    }
+
+    /// One of a few colours that are told apart easily, for colouring the adjacency graph.
+    pub fn graph_color(&self, index: usize) -> Color {
+        let palette = if self.color_blind_safe {
+            [rgb(0x56, 0xB4, 0xE9), rgb(0x00, 0x9E, 0x73), rgb(0x00, 0x72, 0xB2), rgb(0xD5, 0x5E, 0x00), rgb(0xCC, 0x79, 0xA7), rgb(0x99, 0x99, 0x99)]
+        } else {
+            [rgb(0x8D, 0xD3, 0xC7), rgb(0xBE, 0xBA, 0xDA), rgb(0xFB, 0x80, 0x72), rgb(0x80, 0xB1, 0xD3), rgb(0xFD, 0xB4, 0x62), rgb(0xB3, 0xDE, 0x69)]
+        };
+        palette[index % palette.len()]
+    }
+
+    /// Colour for a visit at `recency` between 0 (the oldest visit) and 1 (the latest one).
+    pub fn recency(&self, recency: f32) -> Color {
+        let stops: &[Color] = if self.color_blind_safe {
+            &[rgb(0x44, 0x01, 0x54), rgb(0x3B, 0x52, 0x8B), rgb(0x21, 0x91, 0x8C), rgb(0x5E, 0xC9, 0x62), rgb(0xFD, 0xE7, 0x25)]
+        } else {
+            &[rgb(0xC6, 0xDB, 0xEF), rgb(0x6B, 0xAE, 0xD6), rgb(0x21, 0x71, 0xB5), rgb(0x08, 0x30, 0x6B)]
+        };
+        gradient(stops, recency)
+    }
+
+    /// Labelled colours of the scheme, as shown in the legend of the map. Unvisited countries
+    /// follow the theme rather than the scheme, so their colour is passed in.
+    pub fn legend(&self, unvisited: Color) -> Vec<(String, Color)> {
+        let mut entries = match self.scheme {
+            ColorScheme::Classic | ColorScheme::Neighbours => vec![],
+            ColorScheme::Visited => vec![
+                ("visited".to_string(), self.visited()),
+                ("not visited".to_string(), unvisited),
+            ],
+            ColorScheme::Continent => Continent::ALL.into_iter()
+                .map(|continent| (continent.to_string(), self.continent(continent)))
+                .collect(),
+            ColorScheme::Recency => vec![
+                ("longest ago".to_string(), self.recency(0.0)),
+                ("".to_string(), self.recency(0.5)),
+                ("most recent".to_string(), self.recency(1.0)),
+                ("not visited".to_string(), unvisited),
+            ],
+        };
+        for status in WishlistStatus::ALL {
+            entries.push((status.to_string(), self.wish(status)));
+        }
+        entries
+    }
+}
+
+fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color::from_rgb8(r, g, b)
+}
+
+fn gradient(stops: &[Color], position: f32) -> Color {
+    let position = position.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (position.floor() as usize).min(stops.len() - 2);
+    let (from, to) = (stops[index], stops[index + 1]);
+    let t = position - index as f32;
+    Color::from_rgb(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::Color;
+    use crate::color_scheme::gradient;
+
+    #[test]
+    fn gradient_hits_its_stops() {
+        let stops = [Color::BLACK, Color::from_rgb(0.5, 0.5, 0.5), Color::WHITE];
+        assert_eq!(Color::BLACK, gradient(&stops, 0.0));
+        assert_eq!(Color::from_rgb(0.5, 0.5, 0.5), gradient(&stops, 0.5));
+        assert_eq!(Color::WHITE, gradient(&stops, 1.0));
+        assert_eq!(Color::from_rgb(0.25, 0.25, 0.25), gradient(&stops, 0.25));
+    }
+
+    #[test]
+    fn gradient_clamps_out_of_range_positions() {
+        let stops = [Color::BLACK, Color::WHITE];
+        assert_eq!(Color::BLACK, gradient(&stops, -1.0));
+        assert_eq!(Color::WHITE, gradient(&stops, 2.0));
+    }
+}
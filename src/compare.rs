@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+use crate::models::Country;
+
+/// The visited countries of a profile or an export file, identified by their ISO2 code so that
+/// logs from different databases can be compared.
+#[derive(Debug, Clone)]
+pub struct TravelLog {
+    pub name: String,
+    pub visited: HashSet<String>,
+}
+
+impl TravelLog {
+    pub fn new(name: impl Into<String>, visited: impl IntoIterator<Item = Country>) -> Self {
+        Self {
+            name: name.into(),
+            visited: visited.into_iter().map(|country| country.iso2).collect(),
+        }
+    }
+
+    pub fn has_visited(&self, country: &Country) -> bool {
+        self.visited.contains(&country.iso2)
+    }
+}
+
+/// Which of two compared travel logs contain a visit to a country.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonSide {
+    Both,
+    OnlyFirst,
+    OnlySecond,
+}
+
+impl ComparisonSide {
+    pub const ALL: [ComparisonSide; 3] = [ComparisonSide::Both, ComparisonSide::OnlyFirst, ComparisonSide::OnlySecond];
+
+    pub fn of(visited_first: bool, visited_second: bool) -> Option<Self> {
+        match (visited_first, visited_second) {
+            (true, true) => Some(ComparisonSide::Both),
+            (true, false) => Some(ComparisonSide::OnlyFirst),
+            (false, true) => Some(ComparisonSide::OnlySecond),
+            (false, false) => None,
+        }
+    }
+
+    pub fn title(&self, first: &str, second: &str) -> String {
+        match self {
+            ComparisonSide::Both => "Both".to_string(),
+            ComparisonSide::OnlyFirst => format!("Only {}", first),
+            ComparisonSide::OnlySecond => format!("Only {}", second),
+        }
+    }
+}
+
+/// Sorts the countries by the side of the comparison they were visited on. Countries nobody
+/// visited are left out, each side is ordered by name.
+pub fn compare<'a>(
+    countries: impl IntoIterator<Item = &'a Country>,
+    visited_first: impl Fn(&Country) -> bool,
+    visited_second: impl Fn(&Country) -> bool,
+) -> Vec<(ComparisonSide, Vec<&'a Country>)> {
+    let mut countries: Vec<&Country> = countries.into_iter().collect();
+    countries.sort_by(|a, b| a.name.cmp(&b.name));
+    ComparisonSide::ALL.into_iter()
+        .map(|side| {
+            let on_side = countries.iter()
+                .copied()
+                .filter(|country| ComparisonSide::of(visited_first(country), visited_second(country)) == Some(side))
+                .collect();
+            (side, on_side)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compare::{compare, ComparisonSide, TravelLog};
+    use crate::models::Country;
+
+    fn country(id: i32, name: &str, iso2: &str) -> Country {
+        Country {
+            id,
+            name: name.to_string(),
+            iso2: iso2.to_string(),
+            iso3: format!("{}X", iso2),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn countries_are_split_by_side() {
+        let countries = vec![
+            country(1, "Germany", "DE"),
+            country(2, "France", "FR"),
+            country(3, "Italy", "IT"),
+            country(4, "Spain", "ES"),
+            country(5, "Austria", "AT"),
+        ];
+        let first = TravelLog::new("a", vec![countries[0].clone(), countries[1].clone(), countries[4].clone()]);
+        let second = TravelLog::new("b", vec![countries[1].clone(), countries[2].clone()]);
+        let comparison = compare(&countries, |c| first.has_visited(c), |c| second.has_visited(c));
+        let names: Vec<(ComparisonSide, Vec<&str>)> = comparison.into_iter()
+            .map(|(side, countries)| (side, countries.into_iter().map(|c| c.name.as_str()).collect()))
+            .collect();
+        assert_eq!(vec![
+            (ComparisonSide::Both, vec!["France"]),
+            (ComparisonSide::OnlyFirst, vec!["Austria", "Germany"]),
+            (ComparisonSide::OnlySecond, vec!["Italy"]),
+        ], names);
+    }
+}
@@ -1,46 +1,74 @@
+use std::collections::{HashMap, HashSet};
 use std::env;
-use clap::Parser;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
 use diesel::sqlite::Sqlite;
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper, SqliteConnection};
-use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::{BoolExpressionMethods, Connection, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper, SqliteConnection};
+use diesel::connection::SimpleConnection;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenv::dotenv;
-use homedir::get_my_home;
-use lazy_static::lazy_static;
-use crate::Args;
+use homedir::{get_my_home, GetHomeError};
+use itertools::Itertools;
 use crate::base_data::COUNTRIES;
-use crate::models::{Country, CountryVisit, NewCountry, NewCountryVisit};
+use crate::goals::Goal;
+use crate::models::{ChangeLogEntry, ChangeSource, Country, CountryNote, CountryVisit, GoalEntry, NewChangeLogEntry, NewCountry, NewCountryVisit, NewGoal, NewProfile, NewWishlistEntry, Operation, Profile, VisitKind, Wish, WishlistEntry};
 use crate::schema::countries::dsl::countries;
 use crate::schema::countries::iso2;
-use crate::schema::country_visits::country_id;
+use crate::schema::country_visits::{country_id, profile_id};
 use crate::schema::country_visits::dsl::country_visits;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
-lazy_static! {
-    static ref POOL: Pool<ConnectionManager<SqliteConnection>> = {
-        dotenv().ok();
-        let args = Args::parse();
-        let path = determine_database_path(&args);
-        let connection_manager = ConnectionManager::<SqliteConnection>::new(path);
-        let pool = Pool::builder().build(connection_manager).expect("Cannot open connection pool");
-        migrate(&mut pool.get().expect("Cannot get connection")).unwrap();
-        let country_count: i64 = crate::schema::countries::table
-            .count()
-            .get_result(&mut pool.get().expect("Cannot get connection"))
-            .unwrap();
-        if country_count == 0 {
-            populate(&mut pool.get().expect("Cannot get connection")).unwrap();
-        }
-        pool
-    };
-}
+const CHANGE_APPLIED: i32 = 0;
+const CHANGE_UNDONE: i32 = 1;
+const CHANGE_DISCARDED: i32 = 2;
+/// Entries that only document what happened, like undos themselves, and are never undone.
+const CHANGE_AUDIT_ONLY: i32 = 3;
+
+diesel::sql_function!(fn last_insert_rowid() -> diesel::sql_types::Integer);
+
+static POOL: OnceLock<Pool<ConnectionManager<SqliteConnection>>> = OnceLock::new();
 
 #[derive(Debug)]
 pub enum DatabaseError {
     R2d2(r2d2::Error),
     Diesel(diesel::result::Error),
-    Unknown(String),
+    Migration(Box<dyn std::error::Error + Send + Sync>),
+    HomeDirectory(GetHomeError),
+    MissingHomeDirectory,
+    InvalidPath(PathBuf),
+    NotInitialized,
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatabaseError::R2d2(e) => write!(f, "cannot connect to the database: {}", e),
+            DatabaseError::Diesel(e) => write!(f, "database query failed: {}", e),
+            DatabaseError::Migration(e) => write!(f, "cannot migrate the database: {}", e),
+            DatabaseError::HomeDirectory(e) => write!(f, "cannot determine the home directory: {}", e),
+            DatabaseError::MissingHomeDirectory => write!(f, "the current user has no home directory, please pass a database path"),
+            DatabaseError::InvalidPath(path) => write!(f, "database path {} is not valid UTF-8", path.display()),
+            DatabaseError::NotInitialized => write!(f, "the database has not been opened yet"),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatabaseError::R2d2(e) => Some(e),
+            DatabaseError::Diesel(e) => Some(e),
+            DatabaseError::Migration(e) => Some(e.as_ref()),
+            DatabaseError::HomeDirectory(e) => Some(e),
+            DatabaseError::MissingHomeDirectory => None,
+            DatabaseError::InvalidPath(_) => None,
+            DatabaseError::NotInitialized => None,
+        }
+    }
 }
 
 impl From<r2d2::Error> for DatabaseError {
@@ -55,36 +83,74 @@ impl From<diesel::result::Error> for DatabaseError {
     }
 }
 
-pub fn connection() -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, DatabaseError> {
-    let connection = POOL.get()?;
-    Ok(connection)
+/// Opens the database at `database_path`, falling back to `DATABASE_URL`, then to
+/// `default_path` from the settings and finally to the database in the home directory. Migrates
+/// it and fills in the base data if it is new. Has to be called before [connection].
+pub fn init(database_path: Option<String>, default_path: Option<String>) -> Result<(), DatabaseError> {
+    dotenv().ok();
+    let path = determine_database_path(database_path, default_path)?;
+    let connection_manager = ConnectionManager::<SqliteConnection>::new(path);
+    let pool = Pool::builder()
+        .connection_customizer(Box::new(ConnectionOptions))
+        .build(connection_manager)?;
+    let mut connection = pool.get()?;
+    migrate(&mut connection)?;
+    let country_count: i64 = crate::schema::countries::table
+        .count()
+        .get_result(&mut connection)?;
+    if country_count == 0 {
+        populate(&mut connection)?;
+    }
+    POOL.get_or_init(|| pool);
+    Ok(())
+}
+
+/// How long a write waits for another connection to finish its transaction before failing.
+const BUSY_TIMEOUT_MS: u32 = 5000;
+
+/// Lets the connections of the pool, which the GUI uses from several threads at once, wait for
+/// each other instead of failing with "database is locked".
+#[derive(Debug)]
+struct ConnectionOptions;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ConnectionOptions {
+    fn on_acquire(&self, connection: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        connection.batch_execute(&format!("PRAGMA busy_timeout = {}; PRAGMA journal_mode = WAL;", BUSY_TIMEOUT_MS))
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
 }
 
-pub fn require_connection() -> PooledConnection<ConnectionManager<SqliteConnection>> {
-    connection().expect("Cannot get database connection")
+pub fn connection() -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, DatabaseError> {
+    let connection = POOL.get()
+        .ok_or(DatabaseError::NotInitialized)?
+        .get()?;
+    Ok(connection)
 }
 
-fn determine_database_path(args: &Args) -> String {
-    let home = get_my_home().unwrap().unwrap();
-    args.database_path.clone()
-        .or(env::var("DATABASE_URL").ok())
-        .unwrap_or(home.join(".country_logger")
-            .join("da.sqlite")
-            .to_str().unwrap()
-            .to_string())
+fn determine_database_path(database_path: Option<String>, default_path: Option<String>) -> Result<String, DatabaseError> {
+    if let Some(path) = database_path.or(env::var("DATABASE_URL").ok()).or(default_path) {
+        return Ok(path);
+    }
+    let home = get_my_home()
+        .map_err(DatabaseError::HomeDirectory)?
+        .ok_or(DatabaseError::MissingHomeDirectory)?;
+    let path = home.join(".country_logger").join("da.sqlite");
+    path.to_str()
+        .map(str::to_string)
+        .ok_or_else(|| DatabaseError::InvalidPath(path.clone()))
 }
 
 fn migrate(connection: &mut impl MigrationHarness<Sqlite>) -> Result<(), DatabaseError> {
     println!("Migrating database...");
-    connection.run_pending_migrations(MIGRATIONS).map_err(|e| DatabaseError::Unknown(e.to_string()))?;
+    connection.run_pending_migrations(MIGRATIONS).map_err(DatabaseError::Migration)?;
     Ok(())
 }
 
 fn populate(connection: &mut SqliteConnection) -> Result<(), diesel::result::Error> {
     use crate::schema::countries;
     println!("Populating new database...");
-    let new_countries: Vec<NewCountry> = COUNTRIES.iter()
-        .map(|(_, c)| NewCountry::from(c.clone()))
+    let new_countries: Vec<NewCountry> = COUNTRIES.values()
+        .map(|c| NewCountry::from(c.clone()))
         .collect();
     for new_country in new_countries.into_iter() {
         diesel::insert_into(countries::table)
@@ -104,9 +170,17 @@ pub fn country_by_iso2(connection: &mut SqliteConnection, iso2_str: &str) -> Res
     Ok(country)
 }
 
-pub fn is_country_visited(connection: &mut SqliteConnection, country: &Country) -> Result<bool, diesel::result::Error> {
+pub fn country_by_id(connection: &mut SqliteConnection, id: i32) -> Result<Option<Country>, diesel::result::Error> {
+    let country = countries.find(id)
+        .select(Country::as_select())
+        .first(connection)
+        .optional()?;
+    Ok(country)
+}
+
+pub fn is_country_visited(connection: &mut SqliteConnection, visiting_profile_id: i32, country: &Country) -> Result<bool, diesel::result::Error> {
     let found = crate::schema::country_visits::table.filter(
-        country_id.eq(country.id)
+        country_id.eq(country.id).and(profile_id.eq(visiting_profile_id))
     )   .limit(1)
         .select(CountryVisit::as_select())
         .load(connection)?;
@@ -120,22 +194,518 @@ pub fn all_countries(connection: &mut SqliteConnection) -> Result<Vec<Country>,
     Ok(found)
 }
 
-pub fn all_countries_with_visit_status(connection: &mut SqliteConnection) -> Result<Vec<(Country, bool)>, diesel::result::Error> {
-    let found: Vec<(Country, bool)> = crate::schema::countries::table
-        .left_join(crate::schema::country_visits::table)
+/// Every country with the kind of visit of the profile, `None` if the profile has not been there.
+pub fn all_countries_with_visit_kind(connection: &mut SqliteConnection, visiting_profile_id: i32) -> Result<Vec<(Country, Option<VisitKind>)>, diesel::result::Error> {
+    use crate::schema::country_visits;
+    let found: Vec<(Country, Option<VisitKind>)> = crate::schema::countries::table
+        .left_join(country_visits::table.on(
+            country_visits::country_id.eq(crate::schema::countries::id)
+                .and(country_visits::profile_id.eq(visiting_profile_id))
+        ))
         .select((Country::as_select(), Option::<CountryVisit>::as_select()))
         .load::<(Country, Option<CountryVisit>)>(connection)?
         .into_iter()
-        .map(|(country, visit)| (country, visit.is_some()))
+        .map(|(country, visit)| (country, visit.map(|visit| visit.kind())))
         .collect();
     Ok(found)
 }
 
-pub fn visit_country(connection: &mut SqliteConnection, country: &Country) -> Result<(), diesel::result::Error> {
+/// Number of profiles that visited each country.
+pub fn visit_row_counts(connection: &mut SqliteConnection) -> Result<HashMap<i32, usize>, diesel::result::Error> {
+    let visited: Vec<i32> = country_visits
+        .select(country_id)
+        .load(connection)?;
+    Ok(visited.into_iter().counts())
+}
+
+/// Replaces the personal description of the country, `None` removes it.
+pub fn set_description(connection: &mut SqliteConnection, change_set: ChangeSet, described_country_id: i32, description: Option<&str>) -> Result<(), diesel::result::Error> {
+    use crate::schema::countries;
+    connection.transaction(|connection| {
+        let old_description: Option<Option<String>> = countries::table.find(described_country_id)
+            .select(countries::description)
+            .first(connection)
+            .optional()?;
+        let Some(old_description) = old_description.filter(|old| old.as_deref() != description) else {
+            return Ok(());
+        };
+        diesel::update(countries::table.find(described_country_id))
+            .set(countries::description.eq(description))
+            .execute(connection)?;
+        log_value_change(connection, change_set, Operation::SetDescription, CHANGE_APPLIED, Some(described_country_id), old_description, description.map(str::to_string))
+    })
+}
+
+pub fn all_visits(connection: &mut SqliteConnection, visiting_profile_id: i32) -> Result<Vec<CountryVisit>, diesel::result::Error> {
+    let found = country_visits
+        .filter(profile_id.eq(visiting_profile_id))
+        .select(CountryVisit::as_select())
+        .load(connection)?;
+    Ok(found)
+}
+
+pub fn visited_countries(connection: &mut SqliteConnection, visiting_profile_id: i32) -> Result<Vec<Country>, diesel::result::Error> {
+    let found = country_visits
+        .inner_join(countries)
+        .filter(profile_id.eq(visiting_profile_id))
+        .select(Country::as_select())
+        .load(connection)?;
+    Ok(found)
+}
+
+/// Ids of the visited countries of every profile, including profiles without any visits.
+/// Countries the profile only passed through in transit are left out unless `include_transit`.
+pub fn visited_country_ids_by_profile(connection: &mut SqliteConnection, include_transit: bool) -> Result<Vec<(Profile, HashSet<i32>)>, diesel::result::Error> {
+    let visits = country_visits
+        .select(CountryVisit::as_select())
+        .load(connection)?;
+    let visited = all_profiles(connection)?.into_iter()
+        .map(|profile| {
+            let visited_ids = visits.iter()
+                .filter(|visit| visit.profile_id == profile.id)
+                .filter(|visit| include_transit || visit.kind() != VisitKind::Transit)
+                .map(|visit| visit.country_id)
+                .collect();
+            (profile, visited_ids)
+        })
+        .collect();
+    Ok(visited)
+}
+
+pub fn all_profiles(connection: &mut SqliteConnection) -> Result<Vec<Profile>, diesel::result::Error> {
+    use crate::schema::profiles;
+    let found = profiles::table
+        .order(profiles::name.asc())
+        .select(Profile::as_select())
+        .load(connection)?;
+    Ok(found)
+}
+
+pub fn profile_by_name(connection: &mut SqliteConnection, profile_name: &str) -> Result<Option<Profile>, diesel::result::Error> {
+    use crate::schema::profiles;
+    profiles::table
+        .filter(profiles::name.eq(profile_name))
+        .select(Profile::as_select())
+        .first(connection)
+        .optional()
+}
+
+/// Looks up the profile with the given name and creates it if there is none yet.
+pub fn profile_by_name_or_create(connection: &mut SqliteConnection, profile_name: &str) -> Result<Profile, diesel::result::Error> {
+    use crate::schema::profiles;
+    connection.transaction(|connection| {
+        if let Some(profile) = profile_by_name(connection, profile_name)? {
+            return Ok(profile);
+        }
+        println!("Creating profile {}", profile_name);
+        diesel::insert_into(profiles::table)
+            .values(&NewProfile { name: profile_name.to_string() })
+            .execute(connection)?;
+        profiles::table
+            .filter(profiles::name.eq(profile_name))
+            .select(Profile::as_select())
+            .first(connection)
+    })
+}
+
+/// Changes the kind of the visit, countries that are not visited are left alone.
+pub fn set_visit_kind(connection: &mut SqliteConnection, change_set: ChangeSet, visited_country_id: i32, visit_kind: VisitKind) -> Result<(), diesel::result::Error> {
+    use crate::schema::country_visits;
+    connection.transaction(|connection| {
+        let visit = country_visits::table
+            .filter(country_visits::profile_id.eq(change_set.profile_id))
+            .filter(country_visits::country_id.eq(visited_country_id));
+        let old_kind: Option<String> = visit
+            .select(country_visits::kind)
+            .first(connection)
+            .optional()?;
+        let Some(old_kind) = old_kind.filter(|old| old != visit_kind.as_str()) else {
+            return Ok(());
+        };
+        diesel::update(visit)
+            .set(country_visits::kind.eq(visit_kind.as_str()))
+            .execute(connection)?;
+        log_value_change(connection, change_set, Operation::SetVisitKind, CHANGE_APPLIED, Some(visited_country_id), Some(old_kind), Some(visit_kind.as_str().to_string()))
+    })
+}
+
+/// When the profile visited each country, leaving out visits without a date.
+pub fn visit_dates(connection: &mut SqliteConnection, visiting_profile_id: i32) -> Result<HashMap<i32, NaiveDate>, diesel::result::Error> {
+    use crate::schema::country_visits;
+    let dates: Vec<(i32, Option<NaiveDate>)> = country_visits::table
+        .filter(country_visits::profile_id.eq(visiting_profile_id))
+        .select((country_visits::country_id, country_visits::visited_on))
+        .load(connection)?;
+    Ok(dates.into_iter()
+        .filter_map(|(visited_country_id, date)| date.map(|date| (visited_country_id, date)))
+        .collect())
+}
+
+/// Changes the date of the visit, countries that are not visited are left alone.
+pub fn set_visit_date(connection: &mut SqliteConnection, change_set: ChangeSet, visited_country_id: i32, date: Option<NaiveDate>) -> Result<(), diesel::result::Error> {
+    use crate::schema::country_visits;
+    connection.transaction(|connection| {
+        let visit = country_visits::table
+            .filter(country_visits::profile_id.eq(change_set.profile_id))
+            .filter(country_visits::country_id.eq(visited_country_id));
+        let old_date: Option<Option<NaiveDate>> = visit
+            .select(country_visits::visited_on)
+            .first(connection)
+            .optional()?;
+        let Some(old_date) = old_date.filter(|old| *old != date) else {
+            return Ok(());
+        };
+        diesel::update(visit)
+            .set(country_visits::visited_on.eq(date))
+            .execute(connection)?;
+        let log_value = |date: Option<NaiveDate>| date.map(|date| date.to_string());
+        log_value_change(connection, change_set, Operation::SetVisitDate, CHANGE_APPLIED, Some(visited_country_id), log_value(old_date), log_value(date))
+    })
+}
+
+pub fn all_notes(connection: &mut SqliteConnection, noting_profile_id: i32) -> Result<Vec<CountryNote>, diesel::result::Error> {
+    use crate::schema::country_notes;
+    let found = country_notes::table
+        .filter(country_notes::profile_id.eq(noting_profile_id))
+        .order(country_notes::id.asc())
+        .select(CountryNote::as_select())
+        .load(connection)?;
+    Ok(found)
+}
+
+pub fn all_wishes(connection: &mut SqliteConnection, wishing_profile_id: i32) -> Result<Vec<WishlistEntry>, diesel::result::Error> {
+    use crate::schema::wishlist_entries;
+    let found = wishlist_entries::table
+        .filter(wishlist_entries::profile_id.eq(wishing_profile_id))
+        .select(WishlistEntry::as_select())
+        .load(connection)?;
+    Ok(found)
+}
+
+/// Puts the country on the wishlist of the profile, replacing an earlier wish, or removes it
+/// from the wishlist if `wish` is `None`.
+pub fn set_wish(connection: &mut SqliteConnection, change_set: ChangeSet, wished_country_id: i32, wish: Option<Wish>) -> Result<(), diesel::result::Error> {
+    use crate::schema::wishlist_entries;
+    connection.transaction(|connection| {
+        let entry = wishlist_entries::table
+            .filter(wishlist_entries::profile_id.eq(change_set.profile_id))
+            .filter(wishlist_entries::country_id.eq(wished_country_id));
+        let old_wish = entry
+            .select(WishlistEntry::as_select())
+            .first(connection)
+            .optional()?
+            .and_then(|entry| entry.wish());
+        if old_wish == wish {
+            return Ok(());
+        }
+        diesel::delete(entry).execute(connection)?;
+        if let Some(wish) = wish {
+            diesel::insert_into(wishlist_entries::table)
+                .values(&NewWishlistEntry {
+                    profile_id: change_set.profile_id,
+                    country_id: wished_country_id,
+                    status: wish.status.as_str().to_string(),
+                    priority: wish.priority,
+                    target_year: wish.target_year,
+                })
+                .execute(connection)?;
+        }
+        let log_value = |wish: Option<Wish>| wish.map(|wish| wish.to_log_value());
+        log_value_change(connection, change_set, Operation::SetWish, CHANGE_APPLIED, Some(wished_country_id), log_value(old_wish), log_value(wish))
+    })
+}
+
+pub fn all_goals(connection: &mut SqliteConnection, goal_profile_id: i32) -> Result<Vec<GoalEntry>, diesel::result::Error> {
+    use crate::schema::goals;
+    let found = goals::table
+        .filter(goals::profile_id.eq(goal_profile_id))
+        .order(goals::id)
+        .select(GoalEntry::as_select())
+        .load(connection)?;
+    Ok(found)
+}
+
+/// Stores a new goal of the profile and returns its id.
+pub fn insert_goal(connection: &mut SqliteConnection, change_set: ChangeSet, goal: Goal) -> Result<i32, diesel::result::Error> {
+    use crate::schema::goals;
+    connection.transaction(|connection| {
+        diesel::insert_into(goals::table)
+            .values(&NewGoal::new(change_set.profile_id, goal))
+            .execute(connection)?;
+        let id = diesel::select(last_insert_rowid()).get_result(connection)?;
+        let entry = goals::table.find(id)
+            .select(GoalEntry::as_select())
+            .first(connection)?;
+        log_value_change(connection, change_set, Operation::AddGoal, CHANGE_APPLIED, None, None, Some(entry.to_log_value()))?;
+        Ok(id)
+    })
+}
+
+pub fn delete_goal(connection: &mut SqliteConnection, change_set: ChangeSet, goal_id: i32) -> Result<(), diesel::result::Error> {
+    use crate::schema::goals;
+    connection.transaction(|connection| {
+        let goal = goals::table.find(goal_id)
+            .filter(goals::profile_id.eq(change_set.profile_id));
+        let Some(entry) = goal.select(GoalEntry::as_select()).first(connection).optional()? else {
+            return Ok(());
+        };
+        diesel::delete(goal).execute(connection)?;
+        log_value_change(connection, change_set, Operation::RemoveGoal, CHANGE_APPLIED, None, Some(entry.to_log_value()), None)
+    })
+}
+
+/// Group of changes that is undone and redone as a whole, together with where and for which
+/// profile it was made.
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeSet {
+    id: i32,
+    profile_id: i32,
+    source: ChangeSource,
+}
+
+/// Starts a new change set. Changes that were undone before can no longer be redone once a new
+/// change set has been started.
+fn begin_change_set(connection: &mut SqliteConnection, changed_profile_id: i32, source: ChangeSource) -> Result<ChangeSet, diesel::result::Error> {
+    use crate::schema::change_log::dsl::{change_log, change_set, profile_id, status};
+    diesel::update(change_log.filter(status.eq(CHANGE_UNDONE).and(profile_id.eq(changed_profile_id))))
+        .set(status.eq(CHANGE_DISCARDED))
+        .execute(connection)?;
+    let last_change_set: Option<i32> = change_log
+        .select(change_set)
+        .order(change_set.desc())
+        .first(connection)
+        .optional()?;
+    Ok(ChangeSet {
+        id: last_change_set.unwrap_or(0) + 1,
+        profile_id: changed_profile_id,
+        source,
+    })
+}
+
+/// Runs the changes in a transaction as a new change set of the profile, see `begin_change_set`.
+/// The transaction takes the write lock up front, so that change sets written at the same time
+/// wait for each other instead of getting the same id.
+pub fn in_change_set<T>(
+    connection: &mut SqliteConnection,
+    changed_profile_id: i32,
+    source: ChangeSource,
+    changes: impl FnOnce(&mut SqliteConnection, ChangeSet) -> Result<T, diesel::result::Error>,
+) -> Result<T, diesel::result::Error> {
+    connection.immediate_transaction(|connection| {
+        let change_set = begin_change_set(connection, changed_profile_id, source)?;
+        changes(connection, change_set)
+    })
+}
+
+pub fn visit_country(connection: &mut SqliteConnection, change_set: ChangeSet, country: &Country) -> Result<(), diesel::result::Error> {
+    connection.transaction(|connection| {
+        if is_country_visited(connection, change_set.profile_id, country)? {
+            return Ok(());
+        }
+        println!("Visiting country {}", country.name);
+        insert_visit(connection, change_set.profile_id, country.id, VisitKind::default().as_str(), Some(Local::now().date_naive()))?;
+        log_visit_change(connection, change_set, Operation::Visit, CHANGE_APPLIED, country.id, true)
+    })
+}
+
+pub fn unvisit_country(connection: &mut SqliteConnection, change_set: ChangeSet, country: &Country) -> Result<(), diesel::result::Error> {
+    connection.transaction(|connection| {
+        if !is_country_visited(connection, change_set.profile_id, country)? {
+            return Ok(());
+        }
+        println!("Unvisiting country {}", country.name);
+        let removed = country_visits
+            .filter(country_id.eq(country.id).and(profile_id.eq(change_set.profile_id)))
+            .select(CountryVisit::as_select())
+            .first(connection)?;
+        delete_visits(connection, change_set.profile_id, country.id)?;
+        log_value_change(connection, change_set, Operation::Unvisit, CHANGE_APPLIED, Some(country.id), Some(removed.to_log_value()), Some(false.to_string()))
+    })
+}
+
+pub fn import_country_with_id(connection: &mut SqliteConnection, change_set: ChangeSet, country: Country) -> Result<(), diesel::result::Error> {
+    use crate::schema::countries;
+    connection.transaction(|connection| {
+        diesel::insert_into(countries::table)
+            .values(&country)
+            .execute(connection)?;
+        log_change(connection, NewChangeLogEntry {
+            change_set: change_set.id,
+            operation: Operation::ImportCountry.as_str().to_string(),
+            country_id: Some(country.id),
+            old_value: None,
+            new_value: Some(country.name.clone()),
+            status: CHANGE_AUDIT_ONLY,
+            source: change_set.source.as_str().to_string(),
+            profile_id: change_set.profile_id,
+        })
+    })
+}
+
+/// Reverts the last change set that has not been undone yet and returns its entries, latest
+/// first. Returns no entries if there is nothing left to undo.
+pub fn undo(connection: &mut SqliteConnection, changed_profile_id: i32, source: ChangeSource) -> Result<Vec<ChangeLogEntry>, diesel::result::Error> {
+    use crate::schema::change_log::dsl::{change_log, change_set, id, profile_id, status};
+    connection.immediate_transaction(|connection| {
+        let last_change_set: Option<i32> = change_log
+            .filter(status.eq(CHANGE_APPLIED).and(profile_id.eq(changed_profile_id)))
+            .select(change_set)
+            .order(change_set.desc())
+            .first(connection)
+            .optional()?;
+        let Some(last_change_set) = last_change_set else {
+            return Ok(vec![]);
+        };
+        let entries = change_log
+            .filter(change_set.eq(last_change_set).and(status.eq(CHANGE_APPLIED)))
+            .order(id.desc())
+            .select(ChangeLogEntry::as_select())
+            .load(connection)?;
+        let undo_set = ChangeSet {
+            id: last_change_set,
+            profile_id: changed_profile_id,
+            source,
+        };
+        for entry in &entries {
+            match (Operation::parse(&entry.operation), entry.country_id) {
+                (Some(Operation::Visit), Some(changed_country_id)) => {
+                    delete_visits(connection, changed_profile_id, changed_country_id)?;
+                    log_visit_change(connection, undo_set, Operation::Undo, CHANGE_AUDIT_ONLY, changed_country_id, false)?;
+                }
+                (Some(Operation::Unvisit), Some(changed_country_id)) => {
+                    let (kind, visited_on) = entry.old_value.as_deref()
+                        .and_then(CountryVisit::from_log_value)
+                        .unwrap_or((VisitKind::default().as_str(), None));
+                    insert_visit(connection, changed_profile_id, changed_country_id, kind, visited_on)?;
+                    log_visit_change(connection, undo_set, Operation::Undo, CHANGE_AUDIT_ONLY, changed_country_id, true)?;
+                }
+                (Some(operation), changed_country_id) if operation.is_value_change() => {
+                    let (replaced, restored) = (entry.new_value.as_deref(), entry.old_value.as_deref());
+                    restore_value(connection, changed_profile_id, operation, changed_country_id, replaced, restored)?;
+                    log_value_change(connection, undo_set, Operation::Undo, CHANGE_AUDIT_ONLY, changed_country_id, replaced.map(str::to_string), restored.map(str::to_string))?;
+                }
+                _ => {}
+            }
+        }
+        diesel::update(change_log.filter(change_set.eq(last_change_set).and(status.eq(CHANGE_APPLIED))))
+            .set(status.eq(CHANGE_UNDONE))
+            .execute(connection)?;
+        Ok(entries)
+    })
+}
+
+/// Applies the change set that was undone last again and returns its entries, oldest first.
+/// Returns no entries if there is nothing to redo.
+pub fn redo(connection: &mut SqliteConnection, changed_profile_id: i32, source: ChangeSource) -> Result<Vec<ChangeLogEntry>, diesel::result::Error> {
+    use crate::schema::change_log::dsl::{change_log, change_set, id, profile_id, status};
+    connection.immediate_transaction(|connection| {
+        let next_change_set: Option<i32> = change_log
+            .filter(status.eq(CHANGE_UNDONE).and(profile_id.eq(changed_profile_id)))
+            .select(change_set)
+            .order(change_set.asc())
+            .first(connection)
+            .optional()?;
+        let Some(next_change_set) = next_change_set else {
+            return Ok(vec![]);
+        };
+        let entries = change_log
+            .filter(change_set.eq(next_change_set).and(status.eq(CHANGE_UNDONE)))
+            .order(id.asc())
+            .select(ChangeLogEntry::as_select())
+            .load(connection)?;
+        let redo_set = ChangeSet {
+            id: next_change_set,
+            profile_id: changed_profile_id,
+            source,
+        };
+        for entry in &entries {
+            match (Operation::parse(&entry.operation), entry.country_id) {
+                (Some(Operation::Visit), Some(changed_country_id)) => {
+                    let visited_on = Local.from_utc_datetime(&entry.changed_at).date_naive();
+                    insert_visit(connection, changed_profile_id, changed_country_id, VisitKind::default().as_str(), Some(visited_on))?;
+                    log_visit_change(connection, redo_set, Operation::Redo, CHANGE_AUDIT_ONLY, changed_country_id, true)?;
+                }
+                (Some(Operation::Unvisit), Some(changed_country_id)) => {
+                    delete_visits(connection, changed_profile_id, changed_country_id)?;
+                    log_visit_change(connection, redo_set, Operation::Redo, CHANGE_AUDIT_ONLY, changed_country_id, false)?;
+                }
+                (Some(operation), changed_country_id) if operation.is_value_change() => {
+                    let (replaced, restored) = (entry.old_value.as_deref(), entry.new_value.as_deref());
+                    restore_value(connection, changed_profile_id, operation, changed_country_id, replaced, restored)?;
+                    log_value_change(connection, redo_set, Operation::Redo, CHANGE_AUDIT_ONLY, changed_country_id, replaced.map(str::to_string), restored.map(str::to_string))?;
+                }
+                _ => {}
+            }
+        }
+        diesel::update(change_log.filter(change_set.eq(next_change_set).and(status.eq(CHANGE_UNDONE))))
+            .set(status.eq(CHANGE_APPLIED))
+            .execute(connection)?;
+        Ok(entries)
+    })
+}
+
+/// The latest entries of the change log together with the country they affected, if any,
+/// newest first.
+pub fn recent_changes(connection: &mut SqliteConnection, changed_profile_id: i32, limit: i64) -> Result<Vec<(ChangeLogEntry, Option<Country>)>, diesel::result::Error> {
+    use crate::schema::change_log;
+    let found = change_log::table
+        .left_join(crate::schema::countries::table)
+        .filter(change_log::profile_id.eq(changed_profile_id))
+        .order(change_log::id.desc())
+        .limit(limit)
+        .select((ChangeLogEntry::as_select(), Option::<Country>::as_select()))
+        .load(connection)?;
+    Ok(found)
+}
+
+/// When each country was last marked as visited by the profile, according to the change log.
+/// Countries visited before the change log existed are missing.
+pub fn last_visit_times(connection: &mut SqliteConnection, changed_profile_id: i32) -> Result<HashMap<i32, NaiveDateTime>, diesel::result::Error> {
+    use crate::schema::change_log;
+    let changes: Vec<(Option<i32>, NaiveDateTime)> = change_log::table
+        .filter(change_log::profile_id.eq(changed_profile_id))
+        .filter(change_log::operation.eq_any([Operation::Visit.as_str(), Operation::Undo.as_str(), Operation::Redo.as_str()]))
+        .filter(change_log::old_value.eq("false").and(change_log::new_value.eq("true")))
+        .filter(change_log::status.eq_any([CHANGE_APPLIED, CHANGE_AUDIT_ONLY]))
+        .order(change_log::id.asc())
+        .select((change_log::country_id, change_log::changed_at))
+        .load(connection)?;
+    Ok(changes.into_iter()
+        .filter_map(|(changed_country_id, changed_at)| Some((changed_country_id?, changed_at)))
+        .collect())
+}
+
+/// How often each country was marked as visited, not counting visits that were undone.
+pub fn visit_counts(connection: &mut SqliteConnection, changed_profile_id: i32) -> Result<HashMap<i32, usize>, diesel::result::Error> {
+    use crate::schema::change_log;
+    let visited: Vec<Option<i32>> = change_log::table
+        .filter(change_log::profile_id.eq(changed_profile_id))
+        .filter(change_log::operation.eq(Operation::Visit.as_str()))
+        .filter(change_log::status.eq(CHANGE_APPLIED))
+        .select(change_log::country_id)
+        .load(connection)?;
+    Ok(visited.into_iter().flatten().counts())
+}
+
+/// The whole change log together with the country each entry affected, if any, oldest first.
+pub fn all_changes(connection: &mut SqliteConnection, changed_profile_id: i32) -> Result<Vec<(ChangeLogEntry, Option<Country>)>, diesel::result::Error> {
+    use crate::schema::change_log;
+    let found = change_log::table
+        .left_join(crate::schema::countries::table)
+        .filter(change_log::profile_id.eq(changed_profile_id))
+        .order(change_log::id.asc())
+        .select((ChangeLogEntry::as_select(), Option::<Country>::as_select()))
+        .load(connection)?;
+    Ok(found)
+}
+
+fn insert_visit(connection: &mut SqliteConnection, visiting_profile_id: i32, visited_country_id: i32, visit_kind: &str, visited_on: Option<NaiveDate>) -> Result<(), diesel::result::Error> {
     use crate::schema::country_visits;
-    println!("Visiting country {}", country.name);
     let new_visit = NewCountryVisit {
-        country_id: country.id,
+        profile_id: visiting_profile_id,
+        country_id: visited_country_id,
+        kind: visit_kind.to_string(),
+        visited_on,
     };
     diesel::insert_into(country_visits::table)
         .values(&new_visit)
@@ -143,25 +713,267 @@ pub fn visit_country(connection: &mut SqliteConnection, country: &Country) -> Re
     Ok(())
 }
 
-pub fn unvisit_country(connection: &mut SqliteConnection, country: &Country) -> Result<(), diesel::result::Error> {
-    println!("Unvisiting country {}", country.name);
-    diesel::delete(country_visits.filter(country_id.eq(country.id)))
-        .execute(connection)?;
+/// Puts back a value recorded in the change log by an operation that changes values, replacing
+/// `replaced`. Goals are added again with their old id or removed by the id in `replaced`.
+fn restore_value(connection: &mut SqliteConnection, changed_profile_id: i32, operation: Operation, changed_country_id: Option<i32>, replaced: Option<&str>, restored: Option<&str>) -> Result<(), diesel::result::Error> {
+    use crate::schema::{country_visits, goals, wishlist_entries};
+    match (operation, changed_country_id) {
+        (Operation::SetVisitKind, Some(changed_country_id)) => {
+            let Some(visit_kind) = restored.and_then(VisitKind::parse) else {
+                return Ok(());
+            };
+            diesel::update(country_visits::table
+                    .filter(country_visits::profile_id.eq(changed_profile_id))
+                    .filter(country_visits::country_id.eq(changed_country_id)))
+                .set(country_visits::kind.eq(visit_kind.as_str()))
+                .execute(connection)?;
+        }
+        (Operation::SetVisitDate, Some(changed_country_id)) => {
+            let date: Option<NaiveDate> = restored.and_then(|date| date.parse().ok());
+            diesel::update(country_visits::table
+                    .filter(country_visits::profile_id.eq(changed_profile_id))
+                    .filter(country_visits::country_id.eq(changed_country_id)))
+                .set(country_visits::visited_on.eq(date))
+                .execute(connection)?;
+        }
+        (Operation::SetWish, Some(changed_country_id)) => {
+            diesel::delete(wishlist_entries::table
+                    .filter(wishlist_entries::profile_id.eq(changed_profile_id))
+                    .filter(wishlist_entries::country_id.eq(changed_country_id)))
+                .execute(connection)?;
+            if let Some(wish) = restored.and_then(Wish::from_log_value) {
+                diesel::insert_into(wishlist_entries::table)
+                    .values(&NewWishlistEntry {
+                        profile_id: changed_profile_id,
+                        country_id: changed_country_id,
+                        status: wish.status.as_str().to_string(),
+                        priority: wish.priority,
+                        target_year: wish.target_year,
+                    })
+                    .execute(connection)?;
+            }
+        }
+        (Operation::SetDescription, Some(changed_country_id)) => {
+            diesel::update(countries.find(changed_country_id))
+                .set(crate::schema::countries::description.eq(restored))
+                .execute(connection)?;
+        }
+        (Operation::AddGoal | Operation::RemoveGoal, _) => {
+            if let Some(goal) = replaced.and_then(GoalEntry::from_log_value) {
+                diesel::delete(goals::table.find(goal.id).filter(goals::profile_id.eq(changed_profile_id)))
+                    .execute(connection)?;
+            }
+            if let Some(goal) = restored.and_then(GoalEntry::from_log_value) {
+                diesel::insert_into(goals::table)
+                    .values((
+                        goals::id.eq(goal.id),
+                        goals::profile_id.eq(changed_profile_id),
+                        goals::kind.eq(goal.kind),
+                        goals::count.eq(goal.count),
+                        goals::year.eq(goal.year),
+                        goals::continent.eq(goal.continent),
+                    ))
+                    .execute(connection)?;
+            }
+        }
+        _ => {}
+    }
     Ok(())
 }
 
-pub fn import_country_with_id(connection: &mut SqliteConnection, country: Country) -> Result<(), diesel::result::Error> {
-    use crate::schema::countries;
-    diesel::insert_into(countries::table)
-        .values(&country)
+fn delete_visits(connection: &mut SqliteConnection, visiting_profile_id: i32, visited_country_id: i32) -> Result<(), diesel::result::Error> {
+    diesel::delete(country_visits.filter(country_id.eq(visited_country_id).and(profile_id.eq(visiting_profile_id))))
         .execute(connection)?;
     Ok(())
 }
 
-pub fn import_country_visit(connection: &mut SqliteConnection, visit: CountryVisit) -> Result<(), diesel::result::Error> {
-    use crate::schema::country_visits;
-    diesel::insert_into(country_visits::table)
-        .values(&visit)
+fn log_visit_change(connection: &mut SqliteConnection, change_set: ChangeSet, operation: Operation, status: i32, changed_country_id: i32, visited: bool) -> Result<(), diesel::result::Error> {
+    log_change(connection, NewChangeLogEntry {
+        change_set: change_set.id,
+        operation: operation.as_str().to_string(),
+        country_id: Some(changed_country_id),
+        old_value: Some((!visited).to_string()),
+        new_value: Some(visited.to_string()),
+        status,
+        source: change_set.source.as_str().to_string(),
+        profile_id: change_set.profile_id,
+    })
+}
+
+fn log_value_change(connection: &mut SqliteConnection, change_set: ChangeSet, operation: Operation, status: i32, changed_country_id: Option<i32>, old_value: Option<String>, new_value: Option<String>) -> Result<(), diesel::result::Error> {
+    log_change(connection, NewChangeLogEntry {
+        change_set: change_set.id,
+        operation: operation.as_str().to_string(),
+        country_id: changed_country_id,
+        old_value,
+        new_value,
+        status,
+        source: change_set.source.as_str().to_string(),
+        profile_id: change_set.profile_id,
+    })
+}
+
+fn log_change(connection: &mut SqliteConnection, entry: NewChangeLogEntry) -> Result<(), diesel::result::Error> {
+    use crate::schema::change_log;
+    diesel::insert_into(change_log::table)
+        .values(&entry)
         .execute(connection)?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveDate};
+    use diesel::{Connection, SqliteConnection};
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use itertools::Itertools;
+    use crate::base_data::Continent;
+    use crate::database::{all_countries_with_visit_kind, all_goals, all_wishes, country_by_id, country_by_iso2, ConnectionOptions, delete_goal, in_change_set, insert_goal, migrate, populate, redo, set_description, set_visit_date, set_visit_kind, set_wish, undo, unvisit_country, visit_country, visit_dates};
+    use crate::goals::Goal;
+    use crate::models::{ChangeSource, Country, VisitKind, Wish, WishlistStatus};
+
+    const PROFILE: i32 = 1;
+
+    fn test_database() -> (SqliteConnection, Country) {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        migrate(&mut connection).unwrap();
+        populate(&mut connection).unwrap();
+        let country = country_by_iso2(&mut connection, "NZ").unwrap().unwrap();
+        (connection, country)
+    }
+
+    fn visit_kind(connection: &mut SqliteConnection, country: &Country) -> Option<VisitKind> {
+        all_countries_with_visit_kind(connection, PROFILE).unwrap().into_iter()
+            .find(|(visited, _)| visited.id == country.id)
+            .and_then(|(_, visit_kind)| visit_kind)
+    }
+
+    fn wish(connection: &mut SqliteConnection, country: &Country) -> Option<Wish> {
+        all_wishes(connection, PROFILE).unwrap().into_iter()
+            .find(|entry| entry.country_id == country.id)
+            .and_then(|entry| entry.wish())
+    }
+
+    fn description(connection: &mut SqliteConnection, country: &Country) -> Option<String> {
+        country_by_id(connection, country.id).unwrap().unwrap().description
+    }
+
+    #[test]
+    fn undo_and_redo_restore_changed_values() {
+        let (mut connection, country) = test_database();
+        let old_date = NaiveDate::from_ymd_opt(2019, 3, 4).unwrap();
+        let new_date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let old_wish = Wish::new(WishlistStatus::Wishlist);
+        let new_wish = Wish { target_year: Some(2027), ..Wish::new(WishlistStatus::Planned) };
+        in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            visit_country(connection, change_set, &country)?;
+            set_visit_date(connection, change_set, country.id, Some(old_date))?;
+            set_wish(connection, change_set, country.id, Some(old_wish))?;
+            set_description(connection, change_set, country.id, Some("old"))
+        }).unwrap();
+        in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            set_visit_kind(connection, change_set, country.id, VisitKind::Lived)?;
+            set_visit_date(connection, change_set, country.id, Some(new_date))?;
+            set_wish(connection, change_set, country.id, Some(new_wish))?;
+            set_description(connection, change_set, country.id, Some("new"))
+        }).unwrap();
+
+        assert_eq!(4, undo(&mut connection, PROFILE, ChangeSource::Cli).unwrap().len());
+        assert_eq!(Some(VisitKind::default()), visit_kind(&mut connection, &country));
+        assert_eq!(Some(&old_date), visit_dates(&mut connection, PROFILE).unwrap().get(&country.id));
+        assert_eq!(Some(old_wish), wish(&mut connection, &country));
+        assert_eq!(Some("old".to_string()), description(&mut connection, &country));
+
+        assert_eq!(4, redo(&mut connection, PROFILE, ChangeSource::Cli).unwrap().len());
+        assert_eq!(Some(VisitKind::Lived), visit_kind(&mut connection, &country));
+        assert_eq!(Some(&new_date), visit_dates(&mut connection, PROFILE).unwrap().get(&country.id));
+        assert_eq!(Some(new_wish), wish(&mut connection, &country));
+        assert_eq!(Some("new".to_string()), description(&mut connection, &country));
+    }
+
+    #[test]
+    fn undo_of_unvisit_restores_kind_and_date() {
+        let (mut connection, country) = test_database();
+        let date = NaiveDate::from_ymd_opt(2019, 3, 4).unwrap();
+        in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            visit_country(connection, change_set, &country)?;
+            set_visit_kind(connection, change_set, country.id, VisitKind::Lived)?;
+            set_visit_date(connection, change_set, country.id, Some(date))
+        }).unwrap();
+        in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            unvisit_country(connection, change_set, &country)
+        }).unwrap();
+
+        undo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        assert_eq!(Some(VisitKind::Lived), visit_kind(&mut connection, &country));
+        assert_eq!(Some(&date), visit_dates(&mut connection, PROFILE).unwrap().get(&country.id));
+
+        undo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        assert_eq!(None, visit_kind(&mut connection, &country));
+        redo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        assert_eq!(Some(VisitKind::Lived), visit_kind(&mut connection, &country));
+        assert_eq!(Some(&date), visit_dates(&mut connection, PROFILE).unwrap().get(&country.id));
+    }
+
+    #[test]
+    fn redo_of_visit_dates_it_when_it_was_made() {
+        let (mut connection, country) = test_database();
+        in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            visit_country(connection, change_set, &country)
+        }).unwrap();
+        undo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        redo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        assert_eq!(Some(VisitKind::default()), visit_kind(&mut connection, &country));
+        assert_eq!(Some(&Local::now().date_naive()), visit_dates(&mut connection, PROFILE).unwrap().get(&country.id));
+    }
+
+    #[test]
+    fn change_sets_written_at_the_same_time_wait_for_each_other() {
+        let path = std::env::temp_dir().join(format!("country-logger-test-{}.sqlite", std::process::id()));
+        let pool = Pool::builder()
+            .connection_customizer(Box::new(ConnectionOptions))
+            .build(ConnectionManager::<SqliteConnection>::new(path.to_str().unwrap()))
+            .unwrap();
+        migrate(&mut pool.get().unwrap()).unwrap();
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                std::thread::spawn(move || {
+                    (0..10)
+                        .map(|_| in_change_set(&mut pool.get().unwrap(), PROFILE, ChangeSource::Gui, |connection, change_set| {
+                            insert_goal(connection, change_set, Goal::Continent(Continent::Europe))?;
+                            Ok(change_set.id)
+                        }).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let ids: Vec<i32> = writers.into_iter().flat_map(|writer| writer.join().unwrap()).collect();
+        drop(pool);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+        assert_eq!(ids.len(), ids.iter().unique().count());
+    }
+
+    #[test]
+    fn undo_and_redo_restore_goals() {
+        let (mut connection, _) = test_database();
+        let kept = in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            insert_goal(connection, change_set, Goal::Continent(Continent::Europe))
+        }).unwrap();
+        let added = in_change_set(&mut connection, PROFILE, ChangeSource::Cli, |connection, change_set| {
+            delete_goal(connection, change_set, kept)?;
+            insert_goal(connection, change_set, Goal::CountriesInYear { count: 5, year: 2027 })
+        }).unwrap();
+        let goals_after = all_goals(&mut connection, PROFILE).unwrap();
+        assert_eq!(vec![added], goals_after.iter().map(|goal| goal.id).collect::<Vec<_>>());
+
+        undo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        let goals = all_goals(&mut connection, PROFILE).unwrap();
+        assert_eq!(vec![(kept, Some(Goal::Continent(Continent::Europe)))], goals.iter().map(|goal| (goal.id, goal.goal())).collect::<Vec<_>>());
+
+        redo(&mut connection, PROFILE, ChangeSource::Cli).unwrap();
+        assert_eq!(goals_after, all_goals(&mut connection, PROFILE).unwrap());
+    }
+}
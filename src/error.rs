@@ -0,0 +1,120 @@
+use std::fmt::{Display, Formatter};
+use crate::database::DatabaseError;
+use crate::importer::ImportError;
+use crate::replay::ReplayError;
+use crate::report::ReportError;
+
+/// Error that ends the program. Besides the cause it describes what the program was trying to
+/// do, so it can be shown to the user as is.
+#[derive(Debug)]
+pub struct Error {
+    context: String,
+    kind: ErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    Database(DatabaseError),
+    Import(ImportError),
+    Replay(ReplayError),
+    Report(ReportError),
+    Icon(iced::window::icon::Error),
+    Gui(iced::Error),
+    UnknownProfile(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.context, self.kind)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Database(e) => write!(f, "{}", e),
+            ErrorKind::Import(e) => write!(f, "{}", e),
+            ErrorKind::Replay(e) => write!(f, "{}", e),
+            ErrorKind::Report(e) => write!(f, "{}", e),
+            ErrorKind::Icon(e) => write!(f, "{}", e),
+            ErrorKind::Gui(e) => write!(f, "{}", e),
+            ErrorKind::UnknownProfile(name) => write!(f, "there is no profile named {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ErrorKind::Database(e) => e.source(),
+            ErrorKind::Import(e) => e.source(),
+            ErrorKind::Replay(e) => e.source(),
+            ErrorKind::Report(e) => e.source(),
+            ErrorKind::Icon(e) => e.source(),
+            ErrorKind::Gui(e) => e.source(),
+            ErrorKind::UnknownProfile(_) => None,
+        }
+    }
+}
+
+impl From<DatabaseError> for ErrorKind {
+    fn from(value: DatabaseError) -> Self {
+        ErrorKind::Database(value)
+    }
+}
+
+impl From<diesel::result::Error> for ErrorKind {
+    fn from(value: diesel::result::Error) -> Self {
+        ErrorKind::Database(DatabaseError::Diesel(value))
+    }
+}
+
+impl From<ImportError> for ErrorKind {
+    fn from(value: ImportError) -> Self {
+        ErrorKind::Import(value)
+    }
+}
+
+impl From<ReplayError> for ErrorKind {
+    fn from(value: ReplayError) -> Self {
+        ErrorKind::Replay(value)
+    }
+}
+
+impl From<ReportError> for ErrorKind {
+    fn from(value: ReportError) -> Self {
+        ErrorKind::Report(value)
+    }
+}
+
+impl From<iced::window::icon::Error> for ErrorKind {
+    fn from(value: iced::window::icon::Error) -> Self {
+        ErrorKind::Icon(value)
+    }
+}
+
+impl From<iced::Error> for ErrorKind {
+    fn from(value: iced::Error) -> Self {
+        ErrorKind::Gui(value)
+    }
+}
+
+/// Attaches a description of the failed operation to any error the program knows about.
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T, Error>;
+}
+
+impl<T, E: Into<ErrorKind>> Context<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> Result<T, Error> {
+        self.map_err(|e| Error {
+            context: context.into(),
+            kind: e.into(),
+        })
+    }
+}
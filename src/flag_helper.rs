@@ -5,6 +5,11 @@ use lazy_static::lazy_static;
 
 static FLAG_FILES: Dir = include_dir!("src/assets/flags");
 
+/// The flag as PNG file contents.
+pub fn flag_png(iso2: &str) -> Option<&'static [u8]> {
+    FLAG_FILES.get_file(format!("{}.png", iso2.to_lowercase())).map(|file| file.contents())
+}
+
 lazy_static! {
         pub static ref FLAGS: HashMap<String, Handle> = {
             let mut map = HashMap::new();
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use diesel::SqliteConnection;
+use lazy_static::lazy_static;
+use crate::database;
+use crate::models::Country;
+use crate::rtree::{BoundingBox, RTree};
+use crate::svg_helper::{COUNTRY_POLYGONS, Point, Polygon, SVG_HEIGT, SVG_WIDTH};
+
+/// Robinson projection table in 5° steps of latitude: length of the parallel and distance
+/// from the equator, relative to the equator and the pole.
+const ROBINSON_X: [f64; 19] = [
+    1.0, 0.9986, 0.9954, 0.99, 0.9822, 0.973, 0.96, 0.9427, 0.9216, 0.8962,
+    0.8679, 0.835, 0.7986, 0.7597, 0.7186, 0.6732, 0.6213, 0.5722, 0.5322,
+];
+const ROBINSON_Y: [f64; 19] = [
+    0.0, 0.062, 0.124, 0.186, 0.248, 0.31, 0.372, 0.434, 0.4958, 0.5571,
+    0.6176, 0.6769, 0.7346, 0.7903, 0.8435, 0.8936, 0.9394, 0.9761, 1.0,
+];
+/// Where the border files put the prime meridian and the equator, and how far they put one
+/// degree of longitude on the equator and the pole from the equator, in pixels. Fitted to the
+/// drawn borders, as the files do not say.
+const MAP_CENTER_X: f64 = 986.2;
+const PIXELS_PER_DEGREE: f64 = 5.67;
+const MAP_EQUATOR_Y: f64 = 501.8;
+const POLE_DISTANCE: f64 = 517.9;
+/// How far in pixels a point may lie off the simplified coast lines and still count for the
+/// closest country.
+const COAST_TOLERANCE: f32 = 1.5;
+
+lazy_static! {
+    static ref BORDER_INDEX: RTree<Ring> = RTree::new(COUNTRY_POLYGONS.iter()
+        .flat_map(|(iso2, polygons)| polygons.iter().map(move |polygon| (iso2.as_str(), polygon)))
+        .filter_map(|(iso2, polygon)| {
+            let bounds = BoundingBox::of_polygon(polygon)?;
+            Some((bounds.clone(), Ring { iso2, polygon, area: bounds.area() }))
+        })
+        .collect());
+}
+
+/// One ring of the borders of a country, an island or a hole for an enclave.
+struct Ring {
+    iso2: &'static str,
+    polygon: &'static Polygon,
+    area: f32,
+}
+
+/// Where the WGS84 coordinates lie on the map, in the scaled coordinates of `COUNTRY_POLYGONS`.
+/// `None` if they are not valid coordinates.
+pub fn project(latitude: f64, longitude: f64) -> Option<Point> {
+    if !(-90.0..=90.0).contains(&latitude) || !longitude.is_finite() {
+        return None;
+    }
+    let longitude = (longitude + 180.0).rem_euclid(360.0) - 180.0;
+    let index = ((latitude.abs() / 5.0) as usize).min(ROBINSON_X.len() - 2);
+    let fraction = latitude.abs() / 5.0 - index as f64;
+    let interpolate = |table: &[f64; 19]| table[index] + (table[index + 1] - table[index]) * fraction;
+    let x = MAP_CENTER_X + PIXELS_PER_DEGREE * interpolate(&ROBINSON_X) * longitude;
+    let y = MAP_EQUATOR_Y - POLE_DISTANCE * interpolate(&ROBINSON_Y) * latitude.signum();
+    Some(Point(x as f32 / SVG_WIDTH, y as f32 / SVG_HEIGT))
+}
+
+/// ISO2 code of the country containing the WGS84 coordinates. Points just off the coast count
+/// for the closest country, points on the open sea for none. The borders are only as exact as
+/// the map, where a pixel is about 20 km, and countries without borders on the map are never
+/// found.
+pub fn iso2_at(latitude: f64, longitude: f64) -> Option<&'static str> {
+    let point = project(latitude, longitude)?;
+    country_containing(&point).or_else(|| country_near(&point))
+}
+
+/// The country containing the WGS84 coordinates, see `iso2_at`.
+pub fn country_at(connection: &mut SqliteConnection, latitude: f64, longitude: f64) -> Result<Option<Country>, diesel::result::Error> {
+    match iso2_at(latitude, longitude) {
+        Some(iso2) => database::country_by_iso2(connection, iso2),
+        None => Ok(None),
+    }
+}
+
+/// A country contains the point if an odd number of its rings do, so enclaves cut out of it
+/// belong to the enclave only. Where the borders of countries overlap, the one with the
+/// smallest containing ring wins.
+fn country_containing(point: &Point) -> Option<&'static str> {
+    let mut containing: HashMap<&str, (usize, f32)> = HashMap::new();
+    for ring in BORDER_INDEX.search(&BoundingBox::around(point, &Point(0.0, 0.0))) {
+        if ring.polygon.contains(point) {
+            let (count, area) = containing.entry(ring.iso2).or_insert((0, f32::MAX));
+            *count += 1;
+            *area = area.min(ring.area);
+        }
+    }
+    containing.into_iter()
+        .filter(|(_, (count, _))| count % 2 == 1)
+        .min_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b))
+        .map(|(iso2, _)| iso2)
+}
+
+fn country_near(point: &Point) -> Option<&'static str> {
+    let margin = Point(COAST_TOLERANCE / SVG_WIDTH, COAST_TOLERANCE / SVG_HEIGT);
+    BORDER_INDEX.search(&BoundingBox::around(point, &margin)).into_iter()
+        .map(|ring| (ring.iso2, pixel_distance(point, ring.polygon)))
+        .filter(|(_, distance)| *distance <= COAST_TOLERANCE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(iso2, _)| iso2)
+}
+
+/// Distance from the point to the closest edge of the polygon, in pixels of the map.
+fn pixel_distance(point: &Point, polygon: &Polygon) -> f32 {
+    let to_pixels = |point: &Point| (point.0 * SVG_WIDTH, point.1 * SVG_HEIGT);
+    let (x, y) = to_pixels(point);
+    polygon.iter().zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let ((ax, ay), (bx, by)) = (to_pixels(a), to_pixels(b));
+            let (dx, dy) = (bx - ax, by - ay);
+            let length = dx * dx + dy * dy;
+            let t = if length > 0.0 { (((x - ax) * dx + (y - ay) * dy) / length).clamp(0.0, 1.0) } else { 0.0 };
+            (x - ax - t * dx).hypot(y - ay - t * dy)
+        })
+        .fold(f32::MAX, f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geocode::iso2_at;
+
+    #[test]
+    fn capitals_are_found() {
+        assert_eq!(Some("DE"), iso2_at(52.52, 13.40));
+        assert_eq!(Some("AR"), iso2_at(-34.60, -58.38));
+        assert_eq!(Some("JP"), iso2_at(35.68, 139.69));
+        assert_eq!(Some("US"), iso2_at(38.90, -77.04));
+        assert_eq!(Some("AU"), iso2_at(-35.28, 149.13));
+        assert_eq!(Some("ZA"), iso2_at(-25.75, 28.19));
+    }
+
+    #[test]
+    fn islands_of_the_same_country_are_found() {
+        assert_eq!(Some("ID"), iso2_at(-6.21, 106.85));
+        assert_eq!(Some("ID"), iso2_at(-8.65, 115.22));
+        assert_eq!(Some("ID"), iso2_at(-2.53, 140.72));
+        assert_eq!(Some("SB"), iso2_at(-9.43, 159.95));
+        assert_eq!(Some("SB"), iso2_at(-8.77, 160.70));
+    }
+
+    #[test]
+    fn enclaves_belong_to_the_enclave() {
+        assert_eq!(Some("LS"), iso2_at(-29.60, 28.20));
+        assert_eq!(Some("ZA"), iso2_at(-29.12, 26.21));
+    }
+
+    #[test]
+    fn neighbours_are_told_apart_near_the_border() {
+        assert_eq!(Some("CH"), iso2_at(47.56, 7.59));
+        assert_eq!(Some("FR"), iso2_at(47.75, 7.34));
+        assert_eq!(Some("DE"), iso2_at(47.99, 7.84));
+        assert_eq!(Some("US"), iso2_at(32.72, -117.16));
+        assert_eq!(Some("MX"), iso2_at(32.53, -117.04));
+    }
+
+    #[test]
+    fn the_open_sea_belongs_to_nobody() {
+        assert_eq!(None, iso2_at(0.0, -30.0));
+        assert_eq!(None, iso2_at(100.0, 0.0));
+    }
+}
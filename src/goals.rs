@@ -0,0 +1,79 @@
+use std::fmt::{Display, Formatter};
+use chrono::{Datelike, NaiveDate};
+use crate::base_data::Continent;
+use crate::models::Country;
+
+/// Something a profile sets out to visit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Visit this many countries with a visit date in the year. Each country has one visit
+    /// date, so a country visited again in the year counts as well.
+    CountriesInYear { count: usize, year: i32 },
+    /// Visit every country of the continent.
+    Continent(Continent),
+}
+
+impl Goal {
+    /// How far the visits go towards the goal, as reached and needed count. Each country comes
+    /// with whether it is visited and the day it was visited on, if known.
+    pub fn progress<'a>(&self, countries: impl IntoIterator<Item = (&'a Country, bool, Option<NaiveDate>)>) -> (usize, usize) {
+        match self {
+            Goal::CountriesInYear { count, year } => {
+                let reached = countries.into_iter()
+                    .filter(|(_, visited, date)| *visited && date.map(|date| date.year()) == Some(*year))
+                    .count();
+                (reached.min(*count), *count)
+            }
+            Goal::Continent(continent) => {
+                let on_continent: Vec<bool> = countries.into_iter()
+                    .filter(|(country, _, _)| Continent::of_iso2(&country.iso2) == Some(*continent))
+                    .map(|(_, visited, _)| visited)
+                    .collect();
+                (on_continent.iter().filter(|visited| **visited).count(), on_continent.len())
+            }
+        }
+    }
+}
+
+impl Display for Goal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Goal::CountriesInYear { count: 1, year } => write!(f, "Visit a country in {}", year),
+            Goal::CountriesInYear { count, year } => write!(f, "Visit {} countries in {}", count, year),
+            Goal::Continent(continent) => write!(f, "Complete {}", continent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use crate::base_data::Continent;
+    use crate::goals::Goal;
+    use crate::models::Country;
+
+    fn country(iso2: &str) -> Country {
+        Country {
+            id: 0,
+            name: iso2.to_string(),
+            iso2: iso2.to_string(),
+            iso3: String::new(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn progress_counts_visits_of_the_year_and_continent() {
+        let countries = [country("AR"), country("CL"), country("FR"), country("PE")];
+        let visits = [
+            (true, NaiveDate::from_ymd_opt(2027, 2, 1)),
+            (true, NaiveDate::from_ymd_opt(2019, 5, 6)),
+            (true, NaiveDate::from_ymd_opt(2027, 8, 3)),
+            (false, None),
+        ];
+        let with_visits = || countries.iter().zip(visits).map(|(country, (visited, date))| (country, visited, date));
+        assert_eq!((2, 5), Goal::CountriesInYear { count: 5, year: 2027 }.progress(with_visits()));
+        assert_eq!((2, 3), Goal::Continent(Continent::SouthAmerica).progress(with_visits()));
+        assert_eq!("Complete South America", Goal::Continent(Continent::SouthAmerica).to_string());
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::importer::FormatError;
+use crate::visit_proposals::GpsPoint;
+
+/// Reads the points of the tracks and the waypoints in a GPX file. Routes are left out, as they
+/// are planned rather than travelled.
+pub fn read_gpx(source: &str) -> Result<Vec<GpsPoint>, FormatError> {
+    let mut reader = Reader::from_str(source);
+    reader.trim_text(true);
+    let mut points = vec![];
+    let mut current: Option<GpsPoint> = None;
+    let mut in_time = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(tag) if is_point(&tag) => current = Some(point_at(&tag)?),
+            Event::Empty(tag) if is_point(&tag) => points.push(point_at(&tag)?),
+            Event::Start(tag) if tag.local_name().as_ref() == b"time" => in_time = current.is_some(),
+            Event::Text(text) if in_time => {
+                if let Some(point) = &mut current {
+                    point.date = parse_date(&text.unescape()?);
+                }
+            }
+            Event::End(tag) if tag.local_name().as_ref() == b"time" => in_time = false,
+            Event::End(tag) if matches!(tag.local_name().as_ref(), b"trkpt" | b"wpt") => points.extend(current.take()),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(points)
+}
+
+fn is_point(tag: &BytesStart) -> bool {
+    matches!(tag.local_name().as_ref(), b"trkpt" | b"wpt")
+}
+
+fn point_at(tag: &BytesStart) -> Result<GpsPoint, FormatError> {
+    let coordinate = |name: &str| -> Result<f64, FormatError> {
+        let value = tag.try_get_attribute(name)?
+            .ok_or_else(|| FormatError::InvalidCoordinate(format!("point without {}", name)))?
+            .unescape_value()?;
+        value.trim().parse().map_err(|_| FormatError::InvalidCoordinate(format!("{} {}", name, value)))
+    };
+    Ok(GpsPoint {
+        latitude: coordinate("lat")?,
+        longitude: coordinate("lon")?,
+        date: None,
+    })
+}
+
+/// The day of a GPX timestamp, which is in UTC and usually but not always marked as such.
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(text.trim())
+        .map(|time| time.naive_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .map(|time| time.date())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use crate::gpx::read_gpx;
+
+    #[test]
+    fn track_points_and_waypoints_are_read_with_their_day() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+              <wpt lat="47.5" lon="7.6"><name>Basel</name></wpt>
+              <rte><rtept lat="1.0" lon="2.0"/></rte>
+              <trk><trkseg>
+                <trkpt lat="47.56" lon="7.59"><ele>250</ele><time>2023-05-01T23:30:00Z</time></trkpt>
+                <trkpt lat="47.75" lon="7.34"><time>2023-05-02T08:00:00+02:00</time></trkpt>
+                <trkpt lat="-0.5" lon="-78.5"/>
+              </trkseg></trk>
+            </gpx>"#;
+        let points = read_gpx(gpx).unwrap();
+        let read: Vec<(f64, f64, Option<NaiveDate>)> = points.iter().map(|point| (point.latitude, point.longitude, point.date)).collect();
+        assert_eq!(vec![
+            (47.5, 7.6, None),
+            (47.56, 7.59, NaiveDate::from_ymd_opt(2023, 5, 1)),
+            (47.75, 7.34, NaiveDate::from_ymd_opt(2023, 5, 2)),
+            (-0.5, -78.5, None),
+        ], read);
+    }
+
+    #[test]
+    fn points_need_coordinates() {
+        assert!(read_gpx(r#"<gpx><wpt lat="47.5"/></gpx>"#).is_err());
+        assert!(read_gpx(r#"<gpx><wpt lat="north" lon="7.6"/></gpx>"#).is_err());
+    }
+}
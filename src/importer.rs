@@ -1,12 +1,15 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use chrono::NaiveDate;
 use diesel::SqliteConnection;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use crate::compare::TravelLog;
 use crate::database;
-use crate::database::{country_by_iso2, DatabaseError, is_country_visited, require_connection, visit_country};
-use crate::models::{Country, CountryVisit};
+use crate::database::{ChangeSet, connection, country_by_iso2, DatabaseError, in_change_set, visit_country};
+use crate::models::{ChangeLogEntry, ChangeSource, Country, CountryVisit, Profile, VisitKind, Wish, WishlistEntry, WishlistStatus};
 
 const IMPORT_FILE_VERSION: i32 = 1;
 
@@ -26,6 +29,51 @@ pub enum FormatError {
         actual: i32,
     },
     SerdeJson(serde_json::Error),
+    Xml(quick_xml::Error),
+    InvalidCoordinate(String),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Database(e) => write!(f, "{}", e),
+            ImportError::Io(e) => write!(f, "{}", e),
+            ImportError::Format(e) => write!(f, "invalid import file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Database(e) => Some(e),
+            ImportError::Io(e) => Some(e),
+            ImportError::Format(e) => Some(e),
+        }
+    }
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::MissingMetaLine => write!(f, "missing meta line"),
+            FormatError::MetaLineFormat(message) => write!(f, "malformed meta line: {}", message),
+            FormatError::WrongVersion { expected, actual } => write!(f, "file version is {}, expected {}", actual, expected),
+            FormatError::SerdeJson(e) => write!(f, "{}", e),
+            FormatError::Xml(e) => write!(f, "{}", e),
+            FormatError::InvalidCoordinate(message) => write!(f, "invalid coordinate: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FormatError::SerdeJson(e) => Some(e),
+            FormatError::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<DatabaseError> for ImportError {
@@ -52,24 +100,51 @@ impl From<serde_json::Error> for ImportError {
     }
 }
 
-pub fn simple_import(path: &Path) -> Result<(), ImportError> {
+impl From<FormatError> for ImportError {
+    fn from(value: FormatError) -> Self {
+        ImportError::Format(value)
+    }
+}
+
+impl From<quick_xml::Error> for FormatError {
+    fn from(value: quick_xml::Error) -> Self {
+        FormatError::Xml(value)
+    }
+}
+
+pub fn simple_import(path: &Path, profile: &Profile) -> Result<(), ImportError> {
     let string = fs::read_to_string(path)?;
-    let iso2 = string.split(',');
-    let mut connection = require_connection();
+    let iso2 = parse_simple_import(&string);
+    let mut connection = connection()?;
     let mut countries = vec![];
     for iso2 in iso2 {
         if let Some(country) = country_by_iso2(&mut connection, iso2)? {
             countries.push(country);
         }
     }
-    for country in countries {
-        if !is_country_visited(&mut connection, &country)? {
-            visit_country(&mut connection, &country)?;
+    in_change_set(&mut connection, profile.id, ChangeSource::Import, |connection, change_set| {
+        for country in &countries {
+            visit_country(connection, change_set, country)?;
         }
-    }
+        Ok(())
+    })?;
     Ok(())
 }
 
+fn parse_simple_import(string: &str) -> impl Iterator<Item = &str> {
+    string.split(',').map(str::trim).filter(|iso2| !iso2.is_empty())
+}
+
+/// Writes the ISO2 codes in the format read by `simple_import`.
+pub async fn export_selection(path: PathBuf, iso2: Vec<String>) -> Result<PathBuf, ImportError> {
+    fs::write(&path, format_simple_export(&iso2))?;
+    Ok(path)
+}
+
+fn format_simple_export(iso2: &[String]) -> String {
+    format!("{}\n", iso2.join(","))
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct ImportCountry {
     id: i32,
@@ -107,13 +182,38 @@ impl From<Country> for ImportCountry {
 struct ImportVisit {
     pub id: i32,
     pub country_id: i32,
+    #[serde(default)]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub visited_on: Option<NaiveDate>,
 }
 
-impl From<ImportVisit> for CountryVisit {
-    fn from(value: ImportVisit) -> Self {
-        CountryVisit {
+impl From<CountryVisit> for ImportVisit {
+    fn from(value: CountryVisit) -> Self {
+        Self {
             id: value.id,
             country_id: value.country_id,
+            kind: Some(value.kind),
+            visited_on: value.visited_on,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ImportWish {
+    country_id: i32,
+    status: String,
+    priority: i32,
+    target_year: Option<i32>,
+}
+
+impl From<WishlistEntry> for ImportWish {
+    fn from(value: WishlistEntry) -> Self {
+        Self {
+            country_id: value.country_id,
+            status: value.status,
+            priority: value.priority,
+            target_year: value.target_year,
         }
     }
 }
@@ -125,14 +225,17 @@ struct ImportNotes {}
 struct FullImportFile {
     countries: Vec<ImportCountry>,
     visits: Vec<ImportVisit>,
+    #[serde(default)]
+    wishlist: Vec<ImportWish>,
     notes: HashMap<i32, Vec<ImportNotes>>,
 }
 
-pub fn full_export(path: &Path) -> Result<(), ImportError> {
-    let mut connection = require_connection();
+pub fn full_export(path: &Path, profile: &Profile) -> Result<(), ImportError> {
+    let mut connection = connection()?;
     let full_import_file = FullImportFile {
         countries: database::all_countries(&mut connection)?.into_iter().map(Into::into).collect(),
-        visits: vec![],
+        visits: database::all_visits(&mut connection, profile.id)?.into_iter().map(Into::into).collect(),
+        wishlist: database::all_wishes(&mut connection, profile.id)?.into_iter().map(Into::into).collect(),
         notes: Default::default(),
     };
     let export_contents = format_full_export_file(full_import_file)?;
@@ -140,6 +243,42 @@ pub fn full_export(path: &Path) -> Result<(), ImportError> {
     Ok(())
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct ExportChange {
+    changed_at: String,
+    source: String,
+    operation: String,
+    iso2: Option<String>,
+    country: Option<String>,
+    old_value: Option<String>,
+    new_value: Option<String>,
+}
+
+impl From<(ChangeLogEntry, Option<Country>)> for ExportChange {
+    fn from((entry, country): (ChangeLogEntry, Option<Country>)) -> Self {
+        let (iso2, country) = country.map(|country| (country.iso2, country.name)).unzip();
+        Self {
+            changed_at: entry.changed_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            source: entry.source,
+            operation: entry.operation,
+            iso2,
+            country,
+            old_value: entry.old_value,
+            new_value: entry.new_value,
+        }
+    }
+}
+
+/// Writes the whole change log as JSON, oldest change first.
+pub fn export_change_log(path: &Path, profile: &Profile) -> Result<(), ImportError> {
+    let mut connection = connection()?;
+    let changes: Vec<ExportChange> = database::all_changes(&mut connection, profile.id)?.into_iter()
+        .map(Into::into)
+        .collect();
+    fs::write(path, serde_json::to_string_pretty(&changes)?)?;
+    Ok(())
+}
+
 fn format_full_export_file(full_export_file: FullImportFile) -> Result<String, ImportError> {
     let json = serde_json::to_string_pretty(&full_export_file)?;
     Ok(format!("{}\n{}", format_meta_line(), json))
@@ -149,24 +288,47 @@ fn format_meta_line() -> String {
     format!("{};", IMPORT_FILE_VERSION)
 }
 
-pub fn full_import(path: &Path) -> Result<(), ImportError> {
+pub fn full_import(path: &Path, profile: &Profile) -> Result<(), ImportError> {
     let full_import_file = read_full_import_file(path)?;
     let FullImportFile {
         countries,
         visits,
+        wishlist,
         notes: _notes,
     } = full_import_file;
-    let mut connection = require_connection();
-    for country in countries {
-        import_country_with_id(&mut connection, country)?;
-    }
-    for visit in visits {
-        import_country_visit(&mut connection, visit)?;
-    }
-    // TODO: Add import for notes
+    let mut connection = connection()?;
+    in_change_set(&mut connection, profile.id, ChangeSource::Import, |connection, change_set| {
+        for country in countries {
+            import_country_with_id(connection, change_set, country)?;
+        }
+        for visit in visits {
+            import_country_visit(connection, change_set, visit)?;
+        }
+        for wish in wishlist {
+            import_wish(connection, change_set, wish)?;
+        }
+        // TODO: Add import for notes
+        Ok(())
+    })?;
     Ok(())
 }
 
+/// Reads the visited countries of an export file, named after the file.
+pub fn read_travel_log(path: &Path) -> Result<TravelLog, ImportError> {
+    let FullImportFile { countries, visits, .. } = read_full_import_file(path)?;
+    let name = path.file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    Ok(travel_log_from_file(name, countries, visits))
+}
+
+fn travel_log_from_file(name: String, countries: Vec<ImportCountry>, visits: Vec<ImportVisit>) -> TravelLog {
+    let visited = countries.into_iter()
+        .filter(|country| visits.iter().any(|visit| visit.country_id == country.id))
+        .map(Into::into);
+    TravelLog::new(name, visited)
+}
+
 fn read_full_import_file(path: &Path) -> Result<FullImportFile, ImportError> {
     let file_contents = fs::read_to_string(path)?;
     full_import_file_from_string(&file_contents)
@@ -197,19 +359,48 @@ fn full_import_file_from_string(file_contents: &str) -> Result<FullImportFile, I
     Ok(full_import_file)
 }
 
-fn import_country_with_id(connection: &mut SqliteConnection, country: ImportCountry) -> Result<(), DatabaseError> {
-    database::import_country_with_id(connection, country.into())?;
+fn import_country_with_id(connection: &mut SqliteConnection, change_set: ChangeSet, country: ImportCountry) -> Result<(), diesel::result::Error> {
+    database::import_country_with_id(connection, change_set, country.into())?;
     Ok(())
 }
 
-fn import_country_visit(connection: &mut SqliteConnection, visit: ImportVisit) -> Result<(), DatabaseError> {
-    database::import_country_visit(connection, visit.into())?;
+fn import_country_visit(connection: &mut SqliteConnection, change_set: ChangeSet, visit: ImportVisit) -> Result<(), diesel::result::Error> {
+    if let Some(country) = database::country_by_id(connection, visit.country_id)? {
+        visit_country(connection, change_set, &country)?;
+        if let Some(visit_kind) = visit.kind.as_deref().and_then(VisitKind::parse) {
+            database::set_visit_kind(connection, change_set, country.id, visit_kind)?;
+        }
+        database::set_visit_date(connection, change_set, country.id, visit.visited_on)?;
+    }
+    Ok(())
+}
+
+fn import_wish(connection: &mut SqliteConnection, change_set: ChangeSet, wish: ImportWish) -> Result<(), diesel::result::Error> {
+    let Some(status) = WishlistStatus::parse(&wish.status) else {
+        return Ok(());
+    };
+    if database::country_by_id(connection, wish.country_id)?.is_some() {
+        let wish_to_import = Wish {
+            status,
+            priority: wish.priority,
+            target_year: wish.target_year,
+        };
+        database::set_wish(connection, change_set, wish.country_id, Some(wish_to_import))?;
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::importer::{format_full_export_file, full_import_file_from_string, FullImportFile, ImportCountry, ImportVisit};
+    use chrono::NaiveDate;
+    use crate::importer::{format_full_export_file, format_simple_export, full_import_file_from_string, FullImportFile, ImportCountry, ImportVisit, ImportWish, parse_simple_import, travel_log_from_file};
+
+    #[test]
+    fn exported_selection_can_be_imported() {
+        let selection = vec!["DE".to_string(), "FR".to_string(), "NZ".to_string()];
+        let exported = format_simple_export(&selection);
+        assert_eq!(selection, parse_simple_import(&exported).collect::<Vec<_>>());
+    }
 
     #[test]
     fn import_exported_file_gives_same_result() {
@@ -234,10 +425,22 @@ mod tests {
                 ImportVisit {
                     id: 1,
                     country_id: 2,
+                    kind: Some("transit".to_string()),
+                    visited_on: NaiveDate::from_ymd_opt(2023, 8, 14),
                 },
                 ImportVisit {
                     id: 2,
                     country_id: 1,
+                    kind: None,
+                    visited_on: None,
+                },
+            ],
+            wishlist: vec![
+                ImportWish {
+                    country_id: 1,
+                    status: "planned".to_string(),
+                    priority: 2,
+                    target_year: Some(2027),
                 },
             ],
             notes: Default::default(),
@@ -246,4 +449,42 @@ mod tests {
         let imported = full_import_file_from_string(&exported).unwrap();
         assert_eq!(import_file, imported);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn travel_log_contains_iso2_of_visited_countries() {
+        let countries = vec![
+            ImportCountry {
+                id: 7,
+                name: "Germany".to_string(),
+                iso2: "DE".to_string(),
+                iso3: "DEU".to_string(),
+                description: None,
+            },
+            ImportCountry {
+                id: 8,
+                name: "France".to_string(),
+                iso2: "FR".to_string(),
+                iso3: "FRA".to_string(),
+                description: None,
+            },
+        ];
+        let visits = vec![
+            ImportVisit {
+                id: 1,
+                country_id: 8,
+                kind: None,
+                visited_on: None,
+            },
+        ];
+        let travel_log = travel_log_from_file("trips".to_string(), countries, visits);
+        assert_eq!("trips", travel_log.name);
+        assert_eq!(vec!["FR".to_string()], travel_log.visited.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn files_without_wishlist_can_be_imported() {
+        let file = "1;\n{\"countries\": [], \"visits\": [], \"notes\": {}}";
+        let imported = full_import_file_from_string(file).unwrap();
+        assert!(imported.wishlist.is_empty());
+    }
+}
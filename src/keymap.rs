@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use iced::event::Status;
+use iced::keyboard::{Event, KeyCode, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// Something the user can do from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Selects the previous country in the list, or pans the map up.
+    MoveUp,
+    /// Selects the next country in the list, or pans the map down.
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    ToggleVisited,
+    FocusSearch,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    NextPanel,
+    PreviousPanel,
+    Deselect,
+    Undo,
+    Redo,
+}
+
+impl Action {
+    pub const ALL: [Action; 14] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::ToggleVisited,
+        Action::FocusSearch,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ResetZoom,
+        Action::NextPanel,
+        Action::PreviousPanel,
+        Action::Deselect,
+        Action::Undo,
+        Action::Redo,
+    ];
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::MoveUp => &["Up"],
+            Action::MoveDown => &["Down"],
+            Action::MoveLeft => &["Left"],
+            Action::MoveRight => &["Right"],
+            Action::ToggleVisited => &["Enter", "Space"],
+            Action::FocusSearch => &["Slash", "Ctrl+F"],
+            Action::ZoomIn => &["Plus", "Equals", "Shift+Equals", "NumpadAdd"],
+            Action::ZoomOut => &["Minus", "NumpadSubtract"],
+            Action::ResetZoom => &["0"],
+            Action::NextPanel => &["Tab"],
+            Action::PreviousPanel => &["Shift+Tab"],
+            Action::Deselect => &["Escape"],
+            Action::Undo => &["Ctrl+Z"],
+            Action::Redo => &["Ctrl+Shift+Z", "Ctrl+Y"],
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::MoveUp => write!(f, "move up"),
+            Action::MoveDown => write!(f, "move down"),
+            Action::MoveLeft => write!(f, "move left"),
+            Action::MoveRight => write!(f, "move right"),
+            Action::ToggleVisited => write!(f, "toggle visited"),
+            Action::FocusSearch => write!(f, "search"),
+            Action::ZoomIn => write!(f, "zoom in"),
+            Action::ZoomOut => write!(f, "zoom out"),
+            Action::ResetZoom => write!(f, "reset zoom"),
+            Action::NextPanel => write!(f, "next panel"),
+            Action::PreviousPanel => write!(f, "previous panel"),
+            Action::Deselect => write!(f, "deselect"),
+            Action::Undo => write!(f, "undo"),
+            Action::Redo => write!(f, "redo"),
+        }
+    }
+}
+
+/// A key together with the modifiers held down, written like `Ctrl+Shift+Z`. `Ctrl` stands for
+/// the command key on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyChord {
+    key: KeyCode,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+}
+
+impl KeyChord {
+    fn matches(&self, key: KeyCode, modifiers: Modifiers) -> bool {
+        self.key == key
+            && self.ctrl == modifiers.command()
+            && self.shift == modifiers.shift()
+            && self.alt == modifiers.alt()
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        let mut chord = KeyChord {
+            key: key_code(key).ok_or_else(|| format!("unknown key {:?}", key))?,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        };
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "cmd" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" => chord.alt = true,
+                _ => return Err(format!("unknown modifier {:?}", modifier)),
+            }
+        }
+        Ok(chord)
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (held, name) in [(self.ctrl, "Ctrl+"), (self.shift, "Shift+"), (self.alt, "Alt+")] {
+            if held {
+                write!(f, "{}", name)?;
+            }
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(value: KeyChord) -> Self {
+        value.to_string()
+    }
+}
+
+/// Keys bound to each action. Only the actions the user rebound are stored, every other action
+/// keeps its default keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyBindings(BTreeMap<Action, Vec<KeyChord>>);
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> Vec<KeyChord> {
+        match self.0.get(&action) {
+            Some(keys) => keys.clone(),
+            None => action.default_keys().iter()
+                .map(|key| key.parse().expect("default key bindings are valid"))
+                .collect(),
+        }
+    }
+
+    pub fn action(&self, key: KeyCode, modifiers: Modifiers) -> Option<Action> {
+        Action::ALL.into_iter()
+            .find(|action| self.keys(*action).iter().any(|chord| chord.matches(key, modifiers)))
+    }
+}
+
+/// Whether the event is left for the key bindings. Key presses a focused text input already
+/// handled are not, so typing in a text field does not trigger actions. Changes of the modifier
+/// keys and everything but the keyboard always are.
+pub fn reaches_key_bindings(event: &iced::Event, status: Status) -> bool {
+    match event {
+        iced::Event::Keyboard(Event::ModifiersChanged(_)) => true,
+        iced::Event::Keyboard(_) => status == Status::Ignored,
+        _ => true,
+    }
+}
+
+const KEYS: [(&str, KeyCode); 70] = [
+    ("A", KeyCode::A), ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D), ("E", KeyCode::E),
+    ("F", KeyCode::F), ("G", KeyCode::G), ("H", KeyCode::H), ("I", KeyCode::I), ("J", KeyCode::J),
+    ("K", KeyCode::K), ("L", KeyCode::L), ("M", KeyCode::M), ("N", KeyCode::N), ("O", KeyCode::O),
+    ("P", KeyCode::P), ("Q", KeyCode::Q), ("R", KeyCode::R), ("S", KeyCode::S), ("T", KeyCode::T),
+    ("U", KeyCode::U), ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X), ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0), ("1", KeyCode::Key1), ("2", KeyCode::Key2), ("3", KeyCode::Key3), ("4", KeyCode::Key4),
+    ("5", KeyCode::Key5), ("6", KeyCode::Key6), ("7", KeyCode::Key7), ("8", KeyCode::Key8), ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1), ("F2", KeyCode::F2), ("F3", KeyCode::F3), ("F4", KeyCode::F4), ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6), ("F7", KeyCode::F7), ("F8", KeyCode::F8), ("F9", KeyCode::F9), ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11), ("F12", KeyCode::F12),
+    ("Up", KeyCode::Up), ("Down", KeyCode::Down), ("Left", KeyCode::Left), ("Right", KeyCode::Right),
+    ("Enter", KeyCode::Enter), ("Space", KeyCode::Space), ("Tab", KeyCode::Tab), ("Escape", KeyCode::Escape),
+    ("Backspace", KeyCode::Backspace), ("Delete", KeyCode::Delete), ("Home", KeyCode::Home), ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp), ("PageDown", KeyCode::PageDown),
+    ("Slash", KeyCode::Slash), ("Minus", KeyCode::Minus), ("Plus", KeyCode::Plus), ("Equals", KeyCode::Equals),
+    ("Comma", KeyCode::Comma), ("Period", KeyCode::Period),
+    ("NumpadAdd", KeyCode::NumpadAdd), ("NumpadSubtract", KeyCode::NumpadSubtract),
+];
+
+fn key_code(name: &str) -> Option<KeyCode> {
+    KEYS.iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+fn key_name(key: KeyCode) -> &'static str {
+    KEYS.iter()
+        .find(|(_, key_code)| *key_code == key)
+        .map(|(name, _)| *name)
+        .unwrap_or("?")
+}
+
+#[cfg(test)]
+mod tests {
+    use iced::event::Status;
+    use iced::keyboard::{Event, KeyCode, Modifiers};
+    use crate::keymap::{Action, KeyBindings, KeyChord, key_code, reaches_key_bindings};
+
+    #[test]
+    fn chords_are_parsed_and_written_back() {
+        let chord: KeyChord = "ctrl+shift+z".parse().unwrap();
+        assert_eq!("Ctrl+Shift+Z", chord.to_string());
+        assert_eq!("Slash", "Slash".parse::<KeyChord>().unwrap().to_string());
+        assert!("Ctrl+Nope".parse::<KeyChord>().is_err());
+        assert!("Super+A".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn defaults_apply_to_actions_that_were_not_rebound() {
+        let bindings: KeyBindings = serde_json::from_str(r#"{"toggle_visited": ["V"]}"#).unwrap();
+        assert_eq!(Some(Action::ToggleVisited), bindings.action(KeyCode::V, Modifiers::empty()));
+        assert_eq!(None, bindings.action(KeyCode::Enter, Modifiers::empty()));
+        assert_eq!(Some(Action::MoveDown), bindings.action(KeyCode::Down, Modifiers::empty()));
+    }
+
+    #[test]
+    fn modifiers_have_to_match_exactly() {
+        let bindings = KeyBindings::default();
+        assert_eq!(Some(Action::NextPanel), bindings.action(KeyCode::Tab, Modifiers::empty()));
+        assert_eq!(Some(Action::PreviousPanel), bindings.action(KeyCode::Tab, Modifiers::SHIFT));
+        assert_eq!(None, bindings.action(KeyCode::Tab, Modifiers::ALT));
+    }
+
+    #[test]
+    fn keys_typed_into_a_text_input_trigger_no_actions() {
+        let bindings = KeyBindings::default();
+        for typed in "new zealand".chars() {
+            let name = if typed == ' ' { "Space".to_string() } else { typed.to_string() };
+            let key_code = key_code(&name).unwrap();
+            let event = iced::Event::Keyboard(Event::KeyPressed { key_code, modifiers: Modifiers::empty() });
+            assert!(!reaches_key_bindings(&event, Status::Captured));
+        }
+        let space = iced::Event::Keyboard(Event::KeyPressed { key_code: KeyCode::Space, modifiers: Modifiers::empty() });
+        assert!(reaches_key_bindings(&space, Status::Ignored));
+        assert_eq!(Some(Action::ToggleVisited), bindings.action(KeyCode::Space, Modifiers::empty()));
+        assert!(reaches_key_bindings(&iced::Event::Keyboard(Event::ModifiersChanged(Modifiers::CTRL)), Status::Captured));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(serde_json::from_str::<KeyBindings>(r#"{"undo": ["Ctrl+Ü"]}"#).is_err());
+        assert!(serde_json::from_str::<KeyBindings>(r#"{"fly": ["F"]}"#).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// Which countries the list shows by their visit status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", from = "StoredVisitFilter")]
+pub enum VisitFilter {
+    #[default]
+    All,
+    Visited,
+    Unvisited,
+}
+
+impl VisitFilter {
+    pub const ALL: [VisitFilter; 3] = [VisitFilter::All, VisitFilter::Visited, VisitFilter::Unvisited];
+
+    pub fn accepts(&self, visited: bool) -> bool {
+        match self {
+            VisitFilter::All => true,
+            VisitFilter::Visited => visited,
+            VisitFilter::Unvisited => !visited,
+        }
+    }
+}
+
+impl Display for VisitFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VisitFilter::All => write!(f, "all"),
+            VisitFilter::Visited => write!(f, "visited"),
+            VisitFilter::Unvisited => write!(f, "unvisited"),
+        }
+    }
+}
+
+/// The filter as written by the settings file, which used to only store whether the list is
+/// limited to visited countries.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredVisitFilter {
+    OnlyVisited(bool),
+    Filter(NamedVisitFilter),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum NamedVisitFilter {
+    All,
+    Visited,
+    Unvisited,
+}
+
+impl From<StoredVisitFilter> for VisitFilter {
+    fn from(value: StoredVisitFilter) -> Self {
+        match value {
+            StoredVisitFilter::OnlyVisited(true) | StoredVisitFilter::Filter(NamedVisitFilter::Visited) => VisitFilter::Visited,
+            StoredVisitFilter::OnlyVisited(false) | StoredVisitFilter::Filter(NamedVisitFilter::All) => VisitFilter::All,
+            StoredVisitFilter::Filter(NamedVisitFilter::Unvisited) => VisitFilter::Unvisited,
+        }
+    }
+}
+
+/// Order of the country list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountrySort {
+    #[default]
+    Name,
+    Iso,
+    Continent,
+    /// Most recently visited first.
+    VisitDate,
+    /// Most often visited first.
+    VisitCount,
+}
+
+impl CountrySort {
+    pub const ALL: [CountrySort; 5] = [
+        CountrySort::Name,
+        CountrySort::Iso,
+        CountrySort::Continent,
+        CountrySort::VisitDate,
+        CountrySort::VisitCount,
+    ];
+}
+
+impl Display for CountrySort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CountrySort::Name => write!(f, "by name"),
+            CountrySort::Iso => write!(f, "by ISO code"),
+            CountrySort::Continent => write!(f, "by continent"),
+            CountrySort::VisitDate => write!(f, "by last visit"),
+            CountrySort::VisitCount => write!(f, "by visit count"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::list_options::VisitFilter;
+
+    #[test]
+    fn visit_filter_reads_the_old_checkbox() {
+        assert_eq!(VisitFilter::Visited, serde_json::from_str::<VisitFilter>("true").unwrap());
+        assert_eq!(VisitFilter::All, serde_json::from_str::<VisitFilter>("false").unwrap());
+        assert_eq!(VisitFilter::Unvisited, serde_json::from_str::<VisitFilter>(r#""unvisited""#).unwrap());
+        assert_eq!(r#""unvisited""#, serde_json::to_string(&VisitFilter::Unvisited).unwrap());
+    }
+
+    #[test]
+    fn visit_filter_accepts_matching_countries() {
+        assert!(VisitFilter::All.accepts(false));
+        assert!(VisitFilter::Visited.accepts(true));
+        assert!(!VisitFilter::Visited.accepts(false));
+        assert!(VisitFilter::Unvisited.accepts(false));
+    }
+}
@@ -1,59 +1,149 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use chrono::NaiveDate;
 use clap::Parser;
-use Event::KeyReleased as KeyReleasedEvent;
+use Event::KeyPressed as KeyPressedEvent;
 use iced::{Application, Command, Element, Renderer, Subscription, widget::{column, row}};
 use iced::event::Event::Keyboard as KeyboardEvent;
-use iced::keyboard::{Event, KeyCode};
+use iced::keyboard::{Event, Modifiers};
 use crate::base_data::COUNTRIES;
-use crate::database::{connection, is_country_visited, require_connection, unvisit_country, visit_country};
-use crate::importer::{full_export, full_import, simple_import};
+use crate::database::{connection, DatabaseError};
+use crate::cli::CliCommand;
+use crate::compare::TravelLog;
+use crate::error::{Context, Error, ErrorKind};
+use crate::goals::Goal;
+use crate::importer::{export_selection, full_export, full_import, ImportError, read_travel_log, simple_import};
+use crate::keymap::Action;
+use crate::models::{ChangeLogEntry, Country, VisitKind, Wish};
+use crate::report::{create_report, ReportError};
+use crate::settings::{MIN_WINDOW_SIZE, Settings, SettingsError};
+use crate::state::{AppState, HistoryStep, VisitChange};
 use crate::svg_helper::COUNTRY_POLYGONS;
+use crate::visit_proposals::{propose_from_gpx, propose_from_photos, VisitProposal, write_accepted};
+use crate::widgets::achievements::{AchievementsMessage, AchievementsPanel};
+use crate::widgets::bulk_actions::{BulkActions, BulkActionsMessage};
+use crate::widgets::comparison_list::ComparisonList;
 use crate::widgets::country_info::{CountryInfo, CountryInfoMessage};
 use crate::widgets::country_filter::{CountryFilters, CountryFiltersMessage};
 use crate::widgets::country_list::{CountryList, CountryListMessage};
+use crate::widgets::error_banner::{ErrorBanner, ErrorBannerMessage};
+use crate::widgets::goals_panel::{GoalsPanel, GoalsPanelMessage};
+use crate::widgets::gps_import::{GpsImport, GpsImportMessage};
+use crate::widgets::profile_switcher::{ProfileSwitcher, ProfileSwitcherMessage};
+use crate::widgets::proposal_review::{ProposalReview, ProposalReviewMessage};
+use crate::widgets::recent_activity::RecentActivity;
+use crate::widgets::report_export::{ReportExport, ReportExportMessage};
+use crate::widgets::settings_panel::{SettingsPanel, SettingsPanelMessage};
+use crate::widgets::statistics::{Statistics, StatisticsMessage};
+use crate::widgets::timeline::{Timeline, TimelineMessage};
 use crate::widgets::world_map::{WorldMap, WorldMapCountryFilter, WorldMapMessage};
 
+mod achievements;
+mod adjacency;
 mod base_data;
+mod cli;
+mod color_scheme;
+mod compare;
 mod widgets;
 mod database;
+mod error;
 mod schema;
 mod models;
 mod svg_helper;
 mod importer;
+mod keymap;
+mod list_options;
+mod replay;
+mod report;
+mod rtree;
 mod flag_helper;
+mod geocode;
+mod goals;
+mod gpx;
+mod photos;
+mod search;
+mod settings;
+mod state;
+mod visit_proposals;
 
 const ICON: &[u8] = include_bytes!("assets/globe_icon.png");
 
-fn main() -> iced::Result {
-    let _db_connection = require_connection();
-    println!("found {} svgs for {} countries", COUNTRY_POLYGONS.iter().count(), COUNTRIES.len());
-    if let Some(import_path) = Args::parse().full_import {
-        full_import(Path::new(&import_path)).expect("Error during import");
-    } else if let Some(import_path) = Args::parse().simple_import {
-        simple_import(Path::new(&import_path)).expect("Error during import");
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let args = Args::parse();
+    let settings = Settings::load().unwrap_or_else(|error| {
+        eprintln!("Cannot load settings, using the defaults: {}", error);
+        Settings::default()
+    });
+    database::init(args.database_path.clone(), settings.database_path.clone()).context("Cannot open database")?;
+    let mut connection = connection().context("Cannot open database")?;
+    let profile = if args.create_profile {
+        database::profile_by_name_or_create(&mut connection, &args.profile)
+            .context(format!("Cannot open profile {}", args.profile))?
+    } else {
+        database::profile_by_name(&mut connection, &args.profile)
+            .context(format!("Cannot open profile {}", args.profile))?
+            .ok_or(ErrorKind::UnknownProfile(args.profile.clone()))
+            .context("Cannot open profile")?
+    };
+    println!("found {} svgs for {} countries", COUNTRY_POLYGONS.len(), COUNTRIES.len());
+    if let Some(import_path) = &args.full_import {
+        full_import(Path::new(import_path), &profile).context(format!("Cannot import {}", import_path))?;
+    } else if let Some(import_path) = &args.simple_import {
+        simple_import(Path::new(import_path), &profile).context(format!("Cannot import {}", import_path))?;
     }
-    if let Some(export_file) = Args::parse().full_export {
-        full_export(Path::new(&export_file)).expect("Error during export");
+    if let Some(export_file) = &args.full_export {
+        full_export(Path::new(export_file), &profile).context(format!("Cannot export to {}", export_file))?;
     }
-    if Args::parse().bootstrap_only {
+    if let Some(command) = &args.command {
+        return cli::run(command, &profile);
+    }
+    if args.bootstrap_only {
         return Ok(())
     }
-    let icon = iced::window::icon::from_file_data(ICON, Some(image::ImageFormat::Png)).expect("Cannot load icon");
+    let mut state = AppState::load(&mut connection, profile).context("Cannot load countries")?;
+    drop(connection);
+    if let Some(compare_file) = &args.compare {
+        let travel_log = read_travel_log(Path::new(compare_file)).context(format!("Cannot read {}", compare_file))?;
+        state.compare_with(Some(travel_log));
+    }
+    let icon = iced::window::icon::from_file_data(ICON, Some(image::ImageFormat::Png)).context("Cannot load icon")?;
     MyApp::run(iced::Settings {
         window: iced::window::Settings {
-            size: (1200, 700),
+            size: (settings.window.width, settings.window.height),
+            min_size: Some(MIN_WINDOW_SIZE),
+            position: settings.window.position
+                .map(|(x, y)| iced::window::Position::Specific(x, y))
+                .unwrap_or_default(),
             icon: Some(icon),
             ..Default::default()
         },
         antialiasing: true,
-        ..Default::default()
-    })
+        exit_on_close_request: false,
+        ..iced::Settings::with_flags((state, settings))
+    }).context("Cannot run user interface")
 }
 
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(short = 'd', long)]
     database_path: Option<String>,
+    #[arg(short = 'p', long, default_value = "default")]
+    profile: String,
+    /// Create the profile if there is none with that name yet
+    #[arg(long)]
+    create_profile: bool,
+    #[arg(short = 'c', long)]
+    compare: Option<String>,
     #[arg(short = 'b')]
     bootstrap_only: bool,
     #[arg(short = 's', long = "simple-import")]
@@ -62,13 +152,55 @@ struct Args {
     full_import: Option<String>,
     #[arg(short = 'e', long = "export-file")]
     full_export: Option<String>,
+    #[command(subcommand)]
+    command: Option<CliCommand>,
 }
 
 struct MyApp {
+    state: AppState,
+    settings: Settings,
+    /// The settings as they were last written to the settings file.
+    saved_settings: Settings,
+    settings_panel: SettingsPanel,
+    /// The theme chosen in the settings, with `System` resolved once when it is chosen.
+    theme: iced::Theme,
+    focused_panel: Panel,
+    /// Modifier keys currently held, which turn clicks in the country list into multi-selection.
+    modifiers: Modifiers,
+    bulk_actions: BulkActions,
     country_list: CountryList,
     country_filter: CountryFilters,
     country_info: Option<CountryInfo>,
     world_map: WorldMap,
+    error_banner: ErrorBanner,
+    recent_activity: RecentActivity,
+    comparison_list: ComparisonList,
+    profile_switcher: ProfileSwitcher,
+    statistics: Statistics,
+    timeline: Timeline,
+    report_export: ReportExport,
+    achievements: AchievementsPanel,
+    goals_panel: GoalsPanel,
+    gps_import: GpsImport,
+    proposal_review: Option<ProposalReview>,
+}
+
+/// The part of the window the arrow keys act on, cycled through with Tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Panel {
+    Search,
+    CountryList,
+    Map,
+}
+
+impl Panel {
+    const ALL: [Panel; 3] = [Panel::Search, Panel::CountryList, Panel::Map];
+
+    fn next(self, forward: bool) -> Panel {
+        let index = Panel::ALL.iter().position(|panel| *panel == self).unwrap_or_default();
+        let step = if forward { 1 } else { Panel::ALL.len() - 1 };
+        Panel::ALL[(index + step) % Panel::ALL.len()]
+    }
 }
 
 #[derive(Debug)]
@@ -78,6 +210,33 @@ enum AppMessage {
     CountryFilter(CountryFiltersMessage),
     CountryInfo(CountryInfoMessage),
     WorldMap(WorldMapMessage),
+    VisitChangesWritten(i32, Vec<VisitChange>, Result<(), DatabaseError>),
+    VisitKindWritten(i32, Country, VisitKind, Result<(), DatabaseError>),
+    VisitDateWritten(i32, Country, Option<NaiveDate>, Result<(), DatabaseError>),
+    WishWritten(i32, Country, Option<Wish>, Result<(), DatabaseError>),
+    DescriptionWritten(i32, Country, Option<String>, Result<(), DatabaseError>),
+    HistoryStepped(i32, Result<Box<AppState>, DatabaseError>),
+    RecentActivityLoaded(i32, Result<Vec<(ChangeLogEntry, Option<Country>)>, DatabaseError>),
+    ErrorBanner(ErrorBannerMessage),
+    ProfileSwitcher(ProfileSwitcherMessage),
+    ProfileLoaded(Result<Box<AppState>, DatabaseError>),
+    ComparisonLoaded(i32, Result<TravelLog, DatabaseError>),
+    Statistics(StatisticsMessage),
+    Timeline(TimelineMessage),
+    SettingsSaved(Result<(), SettingsError>),
+    SettingsPanel(SettingsPanelMessage),
+    BulkActions(BulkActionsMessage),
+    SelectionExported(Result<PathBuf, ImportError>),
+    ReportExport(ReportExportMessage),
+    ReportCreated(Result<PathBuf, ReportError>),
+    Achievements(AchievementsMessage),
+    GoalsPanel(GoalsPanelMessage),
+    GoalAdded(i32, Goal, Result<i32, DatabaseError>),
+    GoalRemoved(i32, i32, Result<(), DatabaseError>),
+    GpsImport(GpsImportMessage),
+    ProposalsRead(Result<Vec<VisitProposal>, ImportError>),
+    ProposalReview(ProposalReviewMessage),
+    ProposalsAccepted(i32, Vec<VisitProposal>, Result<(), DatabaseError>),
 }
 
 impl From<CountryListMessage> for AppMessage {
@@ -104,81 +263,764 @@ impl From<WorldMapMessage> for AppMessage {
     }
 }
 
-impl Default for MyApp {
-    fn default() -> Self {
+impl From<ErrorBannerMessage> for AppMessage {
+    fn from(value: ErrorBannerMessage) -> Self {
+        AppMessage::ErrorBanner(value)
+    }
+}
+
+impl From<ProfileSwitcherMessage> for AppMessage {
+    fn from(value: ProfileSwitcherMessage) -> Self {
+        AppMessage::ProfileSwitcher(value)
+    }
+}
+
+impl From<StatisticsMessage> for AppMessage {
+    fn from(value: StatisticsMessage) -> Self {
+        AppMessage::Statistics(value)
+    }
+}
+
+impl From<ReportExportMessage> for AppMessage {
+    fn from(value: ReportExportMessage) -> Self {
+        AppMessage::ReportExport(value)
+    }
+}
+
+impl From<TimelineMessage> for AppMessage {
+    fn from(value: TimelineMessage) -> Self {
+        AppMessage::Timeline(value)
+    }
+}
+
+impl From<BulkActionsMessage> for AppMessage {
+    fn from(value: BulkActionsMessage) -> Self {
+        AppMessage::BulkActions(value)
+    }
+}
+
+impl From<AchievementsMessage> for AppMessage {
+    fn from(value: AchievementsMessage) -> Self {
+        AppMessage::Achievements(value)
+    }
+}
+
+impl From<GoalsPanelMessage> for AppMessage {
+    fn from(value: GoalsPanelMessage) -> Self {
+        AppMessage::GoalsPanel(value)
+    }
+}
+
+impl From<GpsImportMessage> for AppMessage {
+    fn from(value: GpsImportMessage) -> Self {
+        AppMessage::GpsImport(value)
+    }
+}
+
+impl From<ProposalReviewMessage> for AppMessage {
+    fn from(value: ProposalReviewMessage) -> Self {
+        AppMessage::ProposalReview(value)
+    }
+}
+
+impl From<SettingsPanelMessage> for AppMessage {
+    fn from(value: SettingsPanelMessage) -> Self {
+        AppMessage::SettingsPanel(value)
+    }
+}
+
+impl MyApp {
+    fn new(state: AppState, settings: Settings) -> Self {
         Self {
+            state,
             country_list: CountryList::new(),
             country_filter: CountryFilters::new(),
             country_info: None,
-            world_map: WorldMap::new(),
+            world_map: WorldMap::new(settings.map_colors),
+            error_banner: ErrorBanner::new(),
+            recent_activity: RecentActivity::new(),
+            comparison_list: ComparisonList::new(),
+            profile_switcher: ProfileSwitcher::new(),
+            statistics: Statistics::new(),
+            timeline: Timeline::new(),
+            report_export: ReportExport::new(),
+            achievements: AchievementsPanel::new(),
+            goals_panel: GoalsPanel::new(),
+            gps_import: GpsImport::new(),
+            proposal_review: None,
+            saved_settings: settings.clone(),
+            settings_panel: SettingsPanel::new(),
+            theme: settings.theme.iced_theme(),
+            focused_panel: Panel::CountryList,
+            modifiers: Modifiers::empty(),
+            bulk_actions: BulkActions::new(),
+            settings,
         }
     }
 }
 
 impl MyApp {
     fn view_map(&self) -> Element<'_, AppMessage> {
-        self.world_map.view().map(AppMessage::from)
+        column!(
+            self.focus_frame(Panel::Map, self.world_map.view(&self.state).map(AppMessage::from)),
+            self.timeline.view(&self.state).map(AppMessage::from),
+            iced::widget::horizontal_rule(0),
+            self.view_activity_or_comparison(),
+        ).into()
+    }
+
+    /// Sets the panel that has the keyboard apart from the others.
+    fn focus_frame<'a>(&self, panel: Panel, content: Element<'a, AppMessage>) -> Element<'a, AppMessage> {
+        let style = if self.focused_panel == panel {
+            iced::theme::Container::Box
+        } else {
+            iced::theme::Container::Transparent
+        };
+        iced::widget::container(content).style(style).into()
+    }
+
+    fn view_activity_or_comparison(&self) -> Element<'_, AppMessage> {
+        if self.state.compared_with().is_some() {
+            self.comparison_list.view(&self.state)
+        } else {
+            self.recent_activity.view()
+        }
     }
 
     fn view_country_info(&self) -> Element<'_, AppMessage> {
-        if let Some(info) = &self.country_info {
-            info.view().map(AppMessage::from)
+        if let Some(review) = &self.proposal_review {
+            review.view(&self.state).map(AppMessage::from)
+        } else if self.settings_panel.is_open() {
+            self.settings_panel.view(&self.settings).map(AppMessage::from)
+        } else if self.achievements.is_open() {
+            self.achievements.view(&self.state).map(AppMessage::from)
+        } else if self.goals_panel.is_open() {
+            self.goals_panel.view(&self.state).map(AppMessage::from)
+        } else if self.country_list.selected().len() > 1 {
+            let selected: Vec<&Country> = self.country_list.selected().iter()
+                .filter_map(|iso2| self.state.countries().find(|country| &country.iso2 == iso2))
+                .collect();
+            self.bulk_actions.view(&selected).map(AppMessage::from)
+        } else if let Some(info) = &self.country_info {
+            info.view(&self.state).map(AppMessage::from)
         } else {
             column!().into()
         }
     }
 
-    fn update_iced_event(&mut self, event: iced::event::Event) {
-        if let KeyboardEvent(KeyReleasedEvent { key_code, .. }) = event {
-            if key_code == KeyCode::Escape && self.country_info.is_some() {
-                self.country_info = None;
-                self.world_map.update(WorldMapMessage::FilterRemoved);
+    fn update_iced_event(&mut self, event: iced::event::Event) -> Command<AppMessage> {
+        match event {
+            KeyboardEvent(Event::ModifiersChanged(modifiers)) => self.modifiers = modifiers,
+            KeyboardEvent(KeyPressedEvent { key_code, modifiers }) => {
+                if let Some(action) = self.settings.key_bindings.action(key_code, modifiers) {
+                    return self.perform_action(action);
+                }
+            }
+            iced::event::Event::Window(iced::window::Event::Resized { width, height }) => self.settings.window.resize(width, height),
+            iced::event::Event::Window(iced::window::Event::Moved { x, y }) => {
+                self.settings.window.position = Some((x, y));
+            }
+            iced::event::Event::Window(iced::window::Event::CloseRequested) => {
+                if self.settings != self.saved_settings {
+                    if let Err(error) = self.settings.write() {
+                        eprintln!("Cannot save settings: {}", error);
+                    }
+                }
+                return iced::window::close();
+            }
+            _ => {}
+        }
+        Command::none()
+    }
+
+    fn perform_action(&mut self, action: Action) -> Command<AppMessage> {
+        const PAN_STEP: f32 = 0.1;
+        let on_map = self.focused_panel == Panel::Map;
+        match action {
+            Action::MoveUp if on_map => self.world_map.update(WorldMapMessage::Pan(0.0, -PAN_STEP)),
+            Action::MoveDown if on_map => self.world_map.update(WorldMapMessage::Pan(0.0, PAN_STEP)),
+            Action::MoveLeft if on_map => self.world_map.update(WorldMapMessage::Pan(-PAN_STEP, 0.0)),
+            Action::MoveRight if on_map => self.world_map.update(WorldMapMessage::Pan(PAN_STEP, 0.0)),
+            Action::MoveUp => return self.select_neighbour(-1),
+            Action::MoveDown => return self.select_neighbour(1),
+            Action::MoveLeft | Action::MoveRight => {}
+            Action::ToggleVisited => {
+                let countries = self.selected_countries();
+                let visit = !countries.iter().all(|country| self.state.is_visited(country));
+                return self.write_visit_changes(countries.into_iter()
+                    .map(|country| if visit { VisitChange::Visit(country) } else { VisitChange::Unvisit(country) })
+                    .collect());
             }
+            Action::FocusSearch => return self.focus_panel(Panel::Search),
+            Action::ZoomIn => self.world_map.update(WorldMapMessage::ZoomIn),
+            Action::ZoomOut => self.world_map.update(WorldMapMessage::ZoomOut),
+            Action::ResetZoom => self.world_map.update(WorldMapMessage::ResetZoom),
+            Action::NextPanel => return self.focus_panel(self.focused_panel.next(true)),
+            Action::PreviousPanel => return self.focus_panel(self.focused_panel.next(false)),
+            Action::Deselect => self.update_country_list_event(CountryListMessage::Select(None)),
+            Action::Undo => return self.step_history(HistoryStep::Undo),
+            Action::Redo => return self.step_history(HistoryStep::Redo),
         }
+        Command::none()
+    }
+
+    fn step_history(&self, step: HistoryStep) -> Command<AppMessage> {
+        let profile = self.state.profile().clone();
+        let profile_id = profile.id;
+        Command::perform(step.perform(profile), move |result| AppMessage::HistoryStepped(profile_id, result.map(Box::new)))
+    }
+
+    fn focus_panel(&mut self, panel: Panel) -> Command<AppMessage> {
+        self.focused_panel = panel;
+        if panel == Panel::Search {
+            CountryFilters::focus_search()
+        } else {
+            CountryFilters::unfocus_search()
+        }
+    }
+
+    fn select_neighbour(&mut self, offset: isize) -> Command<AppMessage> {
+        let Some(country) = self.country_list.neighbour(&self.state, offset) else {
+            return Command::none();
+        };
+        self.update_country_list_event(CountryListMessage::Select(Some(country)));
+        self.country_list.scroll_to_selected(&self.state)
+    }
+
+    /// Enter in the search field hands the keyboard to the list, selecting the best match if the
+    /// selection is not among the results.
+    fn submit_search(&mut self) -> Command<AppMessage> {
+        let command = self.select_neighbour(0);
+        Command::batch([command, self.focus_panel(Panel::CountryList)])
+    }
+
+    /// A click in the country list, which Ctrl adds to the selection and Shift extends it to.
+    fn click_country(&mut self, country: Country) {
+        let msg = if self.modifiers.command() {
+            CountryListMessage::ToggleSelected(country)
+        } else if self.modifiers.shift() {
+            CountryListMessage::SelectMany(self.country_list.range_to(&self.state, &country))
+        } else {
+            CountryListMessage::Select(Some(country))
+        };
+        self.update_country_list_event(msg);
     }
 
     fn update_country_list_event(&mut self, msg: CountryListMessage) {
-        let mut connection = connection().expect("Cannot get database connection");
-        match &msg {
-            CountryListMessage::Search(_) => {}
-            CountryListMessage::Select(Some(country)) => {
-                self.country_info = Some(CountryInfo::new(country.clone(), is_country_visited(&mut connection, country).expect("Cannot determine country visits")));
-                self.world_map.update(WorldMapMessage::FilterChanged(WorldMapCountryFilter::Include(vec![country.iso2.clone()])));
+        let selection_changed = !matches!(msg, CountryListMessage::Search(_) | CountryListMessage::FilterVisited(_) | CountryListMessage::FilterOnlyWishlist(_)
+            | CountryListMessage::Sort(_) | CountryListMessage::GroupByContinent(_) | CountryListMessage::ToggleGroup(_));
+        self.country_list.update(msg);
+        if selection_changed {
+            self.update_selection();
+        }
+    }
+
+    /// Brings the country info, the highlight on the map and the stored selection in line with
+    /// the countries selected in the list.
+    fn update_selection(&mut self) {
+        let selected = self.selected_countries();
+        self.settings.filters.selected_country = match selected.as_slice() {
+            [country] => Some(country.iso2.clone()),
+            _ => None,
+        };
+        self.country_info = match selected.as_slice() {
+            [country] if self.country_info.as_ref().is_some_and(|info| info.country().iso2 == country.iso2) => self.country_info.take(),
+            [country] => Some(CountryInfo::new(country.clone())),
+            _ => None,
+        };
+        if selected.is_empty() {
+            self.world_map.update(WorldMapMessage::FilterRemoved);
+        } else {
+            let iso2 = selected.into_iter().map(|country| country.iso2).collect();
+            self.world_map.update(WorldMapMessage::FilterChanged(WorldMapCountryFilter::Include(iso2)));
+        }
+    }
+
+    fn selected_countries(&self) -> Vec<Country> {
+        self.country_list.selected().iter()
+            .filter_map(|iso2| self.state.countries().find(|country| &country.iso2 == iso2))
+            .cloned()
+            .collect()
+    }
+
+    fn update_bulk_actions_event(&mut self, msg: BulkActionsMessage) -> Command<AppMessage> {
+        let command = match &msg {
+            BulkActionsMessage::MarkVisited => {
+                self.write_visit_changes(self.selected_countries().into_iter().map(VisitChange::Visit).collect())
+            }
+            BulkActionsMessage::MarkUnvisited => {
+                self.write_visit_changes(self.selected_countries().into_iter().map(VisitChange::Unvisit).collect())
+            }
+            BulkActionsMessage::Export => {
+                let path = PathBuf::from(self.bulk_actions.export_path());
+                let iso2 = self.country_list.selected().to_vec();
+                Command::perform(export_selection(path, iso2), AppMessage::SelectionExported)
             }
-            CountryListMessage::Select(None) => {
-                self.country_info = None;
-                self.world_map.update(WorldMapMessage::FilterRemoved);
+            BulkActionsMessage::ClearSelection => {
+                self.update_country_list_event(CountryListMessage::Select(None));
+                Command::none()
+            }
+            BulkActionsMessage::ExportPath(_) => Command::none(),
+        };
+        self.bulk_actions.update(msg);
+        command
+    }
+
+    fn update_selection_exported(&mut self, result: Result<PathBuf, ImportError>) {
+        match result {
+            Ok(path) => self.bulk_actions.exported(path.display().to_string()),
+            Err(error) => self.error_banner.show(format!("Cannot export selection: {}", error)),
+        }
+    }
+
+    fn update_report_export_event(&mut self, msg: ReportExportMessage) -> Command<AppMessage> {
+        let command = match &msg {
+            ReportExportMessage::Create => {
+                let path = PathBuf::from(self.report_export.path());
+                let create = create_report(path, self.state.profile().clone(), self.report_export.year(), self.world_map.map_colors());
+                Command::perform(create, AppMessage::ReportCreated)
+            }
+            ReportExportMessage::Path(_) | ReportExportMessage::Year(_) => Command::none(),
+        };
+        self.report_export.update(msg);
+        command
+    }
+
+    fn update_report_created(&mut self, result: Result<PathBuf, ReportError>) {
+        match result {
+            Ok(path) => self.report_export.created(path.display().to_string()),
+            Err(error) => self.error_banner.show(format!("Cannot create report: {}", error)),
+        }
+    }
+
+    fn update_gps_import_event(&mut self, msg: GpsImportMessage) -> Command<AppMessage> {
+        let command = match &msg {
+            GpsImportMessage::ImportGpx => {
+                let path = PathBuf::from(self.gps_import.path());
+                Command::perform(propose_from_gpx(vec![path]), AppMessage::ProposalsRead)
+            }
+            GpsImportMessage::ImportPhotos => {
+                let path = PathBuf::from(self.gps_import.path());
+                Command::perform(propose_from_photos(path), AppMessage::ProposalsRead)
+            }
+            GpsImportMessage::Path(_) => Command::none(),
+        };
+        self.gps_import.update(msg);
+        command
+    }
+
+    fn update_proposals_read(&mut self, result: Result<Vec<VisitProposal>, ImportError>) {
+        self.gps_import.finished();
+        match result {
+            Ok(proposals) if proposals.is_empty() => self.error_banner.show("No countries found in the import".to_string()),
+            Ok(proposals) => self.proposal_review = Some(ProposalReview::new(proposals)),
+            Err(error) => self.error_banner.show(format!("Cannot import GPS data: {}", error)),
+        }
+    }
+
+    fn update_proposal_review_event(&mut self, msg: ProposalReviewMessage) -> Command<AppMessage> {
+        match msg {
+            ProposalReviewMessage::Accept => {
+                let accepted = self.proposal_review.take().map(|review| review.accepted()).unwrap_or_default();
+                let profile_id = self.state.profile().id;
+                let write = write_accepted(profile_id, accepted.clone());
+                Command::perform(write, move |result| AppMessage::ProposalsAccepted(profile_id, accepted, result))
+            }
+            ProposalReviewMessage::Discard => {
+                self.proposal_review = None;
+                Command::none()
+            }
+            msg => {
+                if let Some(review) = &mut self.proposal_review {
+                    review.update(msg);
+                }
+                Command::none()
+            }
+        }
+    }
+
+    fn update_proposals_accepted(&mut self, proposals: Vec<VisitProposal>, result: Result<(), DatabaseError>) -> Command<AppMessage> {
+        match result {
+            Ok(()) => {
+                let unlocked = self.unlocked_achievements();
+                for proposal in proposals {
+                    let date = proposal.visit_date(self.state.visit_date(&proposal.country));
+                    self.state.apply(&VisitChange::Visit(proposal.country.clone()));
+                    self.state.set_visit_date(&proposal.country, date);
+                }
+                self.announce_achievements(&unlocked);
+                self.world_map.update(WorldMapMessage::VisitsChanged);
+                self.load_recent_activity()
+            }
+            Err(error) => {
+                self.error_banner.show(format!("Cannot mark imported countries as visited: {}", error));
+                Command::none()
             }
-            CountryListMessage::FilterOnlyVisited(_) => {}
         }
-        self.country_list.update(msg);
     }
 
     fn update_country_filter_event(&mut self, msg: CountryFiltersMessage) {
         match msg {
             CountryFiltersMessage::SearchString(search) => {
+                self.settings.filters.search = search.clone();
                 self.country_list.update(CountryListMessage::Search(search.clone()));
                 self.country_filter.update(CountryFiltersMessage::SearchString(search));
             }
-            CountryFiltersMessage::OnlyVisited(only_visited) => {
-                self.country_list.update(CountryListMessage::FilterOnlyVisited(only_visited));
-                self.country_filter.update(CountryFiltersMessage::OnlyVisited(only_visited));
+            CountryFiltersMessage::Visited(visited) => {
+                self.settings.filters.visited = visited;
+                self.country_list.update(CountryListMessage::FilterVisited(visited));
+                self.country_filter.update(CountryFiltersMessage::Visited(visited));
+            }
+            CountryFiltersMessage::Sort(sort) => {
+                self.settings.filters.sort = sort;
+                self.country_list.update(CountryListMessage::Sort(sort));
+                self.country_filter.update(CountryFiltersMessage::Sort(sort));
+            }
+            CountryFiltersMessage::GroupByContinent(group_by_continent) => {
+                self.settings.filters.group_by_continent = group_by_continent;
+                self.country_list.update(CountryListMessage::GroupByContinent(group_by_continent));
+                self.country_filter.update(CountryFiltersMessage::GroupByContinent(group_by_continent));
+            }
+            CountryFiltersMessage::OnlyWishlist(only_wishlist) => {
+                self.settings.filters.only_wishlist = only_wishlist;
+                self.country_list.update(CountryListMessage::FilterOnlyWishlist(only_wishlist));
+                self.country_filter.update(CountryFiltersMessage::OnlyWishlist(only_wishlist));
+            }
+            CountryFiltersMessage::Submit => {}
+        }
+    }
+
+    fn update_world_map_event(&mut self, msg: WorldMapMessage) -> Command<AppMessage> {
+        if let WorldMapMessage::BoxSelected(iso2, extend) = msg {
+            let countries = self.state.countries()
+                .filter(|country| iso2.contains(&country.iso2))
+                .cloned()
+                .collect();
+            let msg = if extend {
+                CountryListMessage::AddToSelection(countries)
+            } else {
+                CountryListMessage::SelectMany(countries)
+            };
+            self.update_country_list_event(msg);
+            return Command::none();
+        }
+        self.world_map.update(msg);
+        if self.world_map.map_colors() == self.settings.map_colors {
+            return Command::none();
+        }
+        self.settings.map_colors = self.world_map.map_colors();
+        self.save_settings()
+    }
+
+    fn save_settings(&mut self) -> Command<AppMessage> {
+        self.saved_settings = self.settings.clone();
+        Command::perform(self.settings.clone().save(), AppMessage::SettingsSaved)
+    }
+
+    fn update_settings_panel_event(&mut self, msg: SettingsPanelMessage) -> Command<AppMessage> {
+        let command = match &msg {
+            SettingsPanelMessage::Theme(theme) => {
+                self.settings.theme = *theme;
+                self.theme = theme.iced_theme();
+                self.world_map.update(WorldMapMessage::ThemeChanged);
+                self.save_settings()
+            }
+            SettingsPanelMessage::MapColors(map_colors) => self.update_world_map_event(WorldMapMessage::ColorsChanged(*map_colors)),
+            SettingsPanelMessage::SubmitDatabasePath => match self.settings_panel.database_path() {
+                Some(database_path) => {
+                    self.settings.database_path = database_path;
+                    self.save_settings()
+                }
+                None => Command::none(),
+            },
+            SettingsPanelMessage::Toggle => {
+                if !self.settings_panel.is_open() {
+                    self.close_side_panels();
+                }
+                Command::none()
+            }
+            SettingsPanelMessage::DatabasePath(_) => Command::none(),
+        };
+        self.settings_panel.update(msg);
+        command
+    }
+
+    /// Closes the panels shown in place of the country info, so the one being opened is the
+    /// only one.
+    fn close_side_panels(&mut self) {
+        if self.settings_panel.is_open() {
+            self.settings_panel.update(SettingsPanelMessage::Toggle);
+        }
+        if self.achievements.is_open() {
+            self.achievements.update(AchievementsMessage::Toggle);
+        }
+        if self.goals_panel.is_open() {
+            self.goals_panel.update(GoalsPanelMessage::Toggle);
+        }
+    }
+
+    fn update_achievements_event(&mut self, msg: AchievementsMessage) {
+        if matches!(msg, AchievementsMessage::Toggle) && !self.achievements.is_open() {
+            self.close_side_panels();
+        }
+        self.achievements.update(msg);
+    }
+
+    fn update_goals_panel_event(&mut self, msg: GoalsPanelMessage) -> Command<AppMessage> {
+        let command = match &msg {
+            GoalsPanelMessage::Toggle => {
+                if !self.goals_panel.is_open() {
+                    self.close_side_panels();
+                }
+                Command::none()
+            }
+            GoalsPanelMessage::Add => match self.goals_panel.new_goal() {
+                Some(goal) => {
+                    let profile_id = self.state.profile().id;
+                    let write = state::write_goal(profile_id, goal);
+                    Command::perform(write, move |result| AppMessage::GoalAdded(profile_id, goal, result))
+                }
+                None => Command::none(),
+            },
+            GoalsPanelMessage::Remove(id) => {
+                let (profile_id, id) = (self.state.profile().id, *id);
+                Command::perform(state::delete_goal(profile_id, id), move |result| AppMessage::GoalRemoved(profile_id, id, result))
             }
+            _ => Command::none(),
+        };
+        self.goals_panel.update(msg);
+        command
+    }
+
+    fn update_goal_added(&mut self, goal: Goal, result: Result<i32, DatabaseError>) {
+        match result {
+            Ok(id) => self.state.add_goal(id, goal),
+            Err(error) => self.error_banner.show(format!("Cannot save goal {}: {}", goal, error)),
         }
     }
 
-    fn update_country_info_event(&mut self, msg: CountryInfoMessage) {
-        let mut connection = connection().expect("Cannot get database connection");
-        match &msg {
-            CountryInfoMessage::VisitCountry(country) => {
-                visit_country(&mut connection, country).expect("Cannot visit country");
+    fn update_goal_removed(&mut self, id: i32, result: Result<(), DatabaseError>) {
+        match result {
+            Ok(()) => self.state.remove_goal(id),
+            Err(error) => self.error_banner.show(format!("Cannot remove goal: {}", error)),
+        }
+    }
+
+    /// Announces the achievements unlocked since `before` was taken.
+    fn announce_achievements(&mut self, before: &[&str]) {
+        let names = achievements::unlocked(&self.state).into_iter()
+            .filter(|(achievement, _)| !before.contains(&achievement.id.as_str()))
+            .map(|(achievement, _)| achievement.name.clone());
+        self.achievements.announce(names);
+    }
+
+    fn unlocked_achievements(&self) -> Vec<&'static str> {
+        achievements::unlocked(&self.state).into_iter()
+            .map(|(achievement, _)| achievement.id.as_str())
+            .collect()
+    }
+
+    /// Brings back the filters and the selection of the last session.
+    fn restore_filters(&mut self) {
+        let filters = self.settings.filters.clone();
+        self.update_country_filter_event(CountryFiltersMessage::SearchString(filters.search));
+        self.update_country_filter_event(CountryFiltersMessage::Visited(filters.visited));
+        self.update_country_filter_event(CountryFiltersMessage::Sort(filters.sort));
+        self.update_country_filter_event(CountryFiltersMessage::GroupByContinent(filters.group_by_continent));
+        self.update_country_filter_event(CountryFiltersMessage::OnlyWishlist(filters.only_wishlist));
+        let selected = filters.selected_country
+            .and_then(|iso2| self.state.countries().find(|country| country.iso2 == iso2).cloned());
+        if let Some(country) = selected {
+            self.update_country_list_event(CountryListMessage::Select(Some(country)));
+        }
+    }
+
+    fn update_settings_saved(&mut self, result: Result<(), SettingsError>) {
+        if let Err(error) = result {
+            self.error_banner.show(format!("Cannot save settings: {}", error));
+        }
+    }
+
+    fn update_country_info_event(&mut self, msg: CountryInfoMessage) -> Command<AppMessage> {
+        let profile_id = self.state.profile().id;
+        let command = match msg.clone() {
+            CountryInfoMessage::VisitCountry(country) => self.write_visit_changes(vec![VisitChange::Visit(country)]),
+            CountryInfoMessage::UnvisitCountry(country) => self.write_visit_changes(vec![VisitChange::Unvisit(country)]),
+            CountryInfoMessage::SetVisitKind(country, visit_kind) => {
+                let write = state::write_visit_kind(profile_id, country.id, visit_kind);
+                Command::perform(write, move |result| AppMessage::VisitKindWritten(profile_id, country, visit_kind, result))
+            }
+            CountryInfoMessage::SetVisitDate(country, date) => {
+                let write = state::write_visit_date(profile_id, country.id, date);
+                Command::perform(write, move |result| AppMessage::VisitDateWritten(profile_id, country, date, result))
+            }
+            CountryInfoMessage::SetWish(country, wish) => {
+                let write = state::write_wish(profile_id, country.id, wish);
+                Command::perform(write, move |result| AppMessage::WishWritten(profile_id, country, wish, result))
             }
-            CountryInfoMessage::UnvisitCountry(country) => {
-                unvisit_country(&mut connection, country).expect("Cannot unvisit country");
+            CountryInfoMessage::SetDescription(country, description) => {
+                let write = state::write_description(profile_id, country.id, description.clone());
+                Command::perform(write, move |result| AppMessage::DescriptionWritten(profile_id, country, description, result))
             }
+            CountryInfoMessage::TargetYear(_) | CountryInfoMessage::VisitDate(_) | CountryInfoMessage::Description(_) => Command::none(),
+            CountryInfoMessage::Select(country) => {
+                self.update_country_list_event(CountryListMessage::Select(Some(country)));
+                return Command::none();
+            }
+        };
+        if let Some(info) = &mut self.country_info {
+            info.update(msg);
         }
-        if let Some(country_info) = &mut self.country_info {
-            country_info.update(msg);
+        command
+    }
+
+    fn write_visit_changes(&self, changes: Vec<VisitChange>) -> Command<AppMessage> {
+        if changes.is_empty() {
+            return Command::none();
+        }
+        let profile_id = self.state.profile().id;
+        let write = VisitChange::write_all(changes.clone(), profile_id);
+        Command::perform(write, move |result| AppMessage::VisitChangesWritten(profile_id, changes, result))
+    }
+
+    fn update_visit_kind_written(&mut self, country: Country, visit_kind: VisitKind, result: Result<(), DatabaseError>) {
+        match result {
+            Ok(()) => {
+                self.state.set_visit_kind(&country, visit_kind);
+                self.world_map.update(WorldMapMessage::VisitsChanged);
+            }
+            Err(error) => self.error_banner.show(format!("Cannot save visit kind of {}: {}", country.name, error)),
+        }
+    }
+
+    fn update_visit_date_written(&mut self, country: Country, date: Option<NaiveDate>, result: Result<(), DatabaseError>) {
+        match result {
+            Ok(()) => {
+                self.state.set_visit_date(&country, date);
+                self.update_timeline_event(TimelineMessage::Pause);
+            }
+            Err(error) => self.error_banner.show(format!("Cannot save visit date of {}: {}", country.name, error)),
+        }
+    }
+
+    fn update_timeline_event(&mut self, msg: TimelineMessage) {
+        self.timeline.update(msg, &self.state);
+        self.world_map.update(WorldMapMessage::ShowAsOf(self.timeline.as_of(&self.state)));
+    }
+
+    fn update_wish_written(&mut self, country: Country, wish: Option<Wish>, result: Result<(), DatabaseError>) {
+        match result {
+            Ok(()) => {
+                self.state.set_wish(&country, wish);
+                self.world_map.update(WorldMapMessage::WishlistChanged);
+            }
+            Err(error) => self.error_banner.show(format!("Cannot save wishlist status of {}: {}", country.name, error)),
+        }
+    }
+
+    fn update_description_written(&mut self, country: Country, description: Option<String>, result: Result<(), DatabaseError>) {
+        match result {
+            Ok(()) => self.state.set_description(&country, description),
+            Err(error) => self.error_banner.show(format!("Cannot save description of {}: {}", country.name, error)),
+        }
+    }
+
+    fn update_visit_changes_written(&mut self, changes: Vec<VisitChange>, result: Result<(), DatabaseError>) -> Command<AppMessage> {
+        match result {
+            Ok(()) => {
+                let unlocked = self.unlocked_achievements();
+                for change in &changes {
+                    self.state.apply(change);
+                }
+                self.announce_achievements(&unlocked);
+                self.world_map.update(WorldMapMessage::VisitsChanged);
+                self.load_recent_activity()
+            }
+            Err(error) => {
+                let countries = match changes.as_slice() {
+                    [change] => change.country().name.clone(),
+                    changes => format!("{} countries", changes.len()),
+                };
+                self.error_banner.show(format!("Cannot save visit status of {}: {}", countries, error));
+                Command::none()
+            }
+        }
+    }
+
+    fn update_history_stepped(&mut self, result: Result<Box<AppState>, DatabaseError>) -> Command<AppMessage> {
+        match result {
+            Ok(state) => {
+                let unlocked = self.unlocked_achievements();
+                self.state.reload(*state);
+                self.announce_achievements(&unlocked);
+                self.world_map.update(WorldMapMessage::VisitsChanged);
+                self.load_recent_activity()
+            }
+            Err(error) => {
+                self.error_banner.show(format!("Cannot undo or redo: {}", error));
+                Command::none()
+            }
+        }
+    }
+
+    fn load_recent_activity(&self) -> Command<AppMessage> {
+        let profile_id = self.state.profile().id;
+        Command::perform(state::recent_changes(profile_id), move |result| AppMessage::RecentActivityLoaded(profile_id, result))
+    }
+
+    fn update_recent_activity_loaded(&mut self, result: Result<Vec<(ChangeLogEntry, Option<Country>)>, DatabaseError>) {
+        match result {
+            Ok(changes) => self.recent_activity.set_changes(changes),
+            Err(error) => self.error_banner.show(format!("Cannot load recent activity: {}", error)),
+        }
+    }
+
+    fn update_profile_switcher_event(&mut self, msg: ProfileSwitcherMessage) -> Command<AppMessage> {
+        let command = match &msg {
+            ProfileSwitcherMessage::Select(profile) if profile != self.state.profile() => {
+                Command::perform(state::switch_profile(profile.clone()), |result| AppMessage::ProfileLoaded(result.map(Box::new)))
+            }
+            ProfileSwitcherMessage::Create => match self.profile_switcher.new_profile_name() {
+                Some(name) => Command::perform(state::create_profile(name.to_string()), |result| AppMessage::ProfileLoaded(result.map(Box::new))),
+                None => Command::none(),
+            },
+            ProfileSwitcherMessage::CompareWith(profile) => {
+                let profile_id = self.state.profile().id;
+                Command::perform(state::load_travel_log(profile.clone()), move |result| AppMessage::ComparisonLoaded(profile_id, result))
+            }
+            ProfileSwitcherMessage::StopComparing => {
+                self.state.compare_with(None);
+                self.world_map.update(WorldMapMessage::ComparisonChanged);
+                Command::none()
+            }
+            _ => Command::none(),
+        };
+        self.profile_switcher.update(msg);
+        command
+    }
+
+    fn update_comparison_loaded(&mut self, result: Result<TravelLog, DatabaseError>) {
+        match result {
+            Ok(travel_log) => {
+                self.state.compare_with(Some(travel_log));
+                self.world_map.update(WorldMapMessage::ComparisonChanged);
+            }
+            Err(error) => self.error_banner.show(format!("Cannot load profile to compare with: {}", error)),
+        }
+    }
+
+    fn update_profile_loaded(&mut self, result: Result<Box<AppState>, DatabaseError>) -> Command<AppMessage> {
+        match result {
+            Ok(state) => {
+                self.state = *state;
+                self.update_country_list_event(CountryListMessage::Select(None));
+                self.world_map.update(WorldMapMessage::VisitsChanged);
+                self.load_recent_activity()
+            }
+            Err(error) => {
+                self.error_banner.show(format!("Cannot switch profile: {}", error));
+                Command::none()
+            }
         }
     }
 
@@ -188,48 +1030,114 @@ impl Application for MyApp {
     type Executor = iced::executor::Default;
     type Message = AppMessage;
     type Theme = iced::theme::Theme;
-    type Flags = ();
+    type Flags = (AppState, Settings);
 
-    fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        let commands = vec![];
-        (Default::default(), Command::batch(commands))
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let (state, settings) = flags;
+        let mut app = MyApp::new(state, settings);
+        app.restore_filters();
+        let commands = vec![
+            app.load_recent_activity(),
+        ];
+        (app, Command::batch(commands))
     }
 
     fn title(&self) -> String {
         "Country Logger".into()
     }
 
+    fn theme(&self) -> Self::Theme {
+        self.theme.clone()
+    }
+
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
         match message {
-            AppMessage::Event(event) => self.update_iced_event(event),
+            AppMessage::Event(event) => return self.update_iced_event(event),
+            AppMessage::CountryList(CountryListMessage::Select(Some(country))) => self.click_country(country),
             AppMessage::CountryList(msg) => self.update_country_list_event(msg),
+            AppMessage::CountryFilter(CountryFiltersMessage::Submit) => return self.submit_search(),
             AppMessage::CountryFilter(msg) => self.update_country_filter_event(msg),
-            AppMessage::CountryInfo(msg) => self.update_country_info_event(msg),
-            AppMessage::WorldMap(msg) => self.world_map.update(msg),
+            AppMessage::CountryInfo(msg) => return self.update_country_info_event(msg),
+            AppMessage::WorldMap(msg) => return self.update_world_map_event(msg),
+            // Results of loads and writes made before switching to another profile, whose state is gone.
+            AppMessage::VisitChangesWritten(profile_id, ..) | AppMessage::VisitKindWritten(profile_id, ..) | AppMessage::VisitDateWritten(profile_id, ..)
+            | AppMessage::WishWritten(profile_id, ..) | AppMessage::DescriptionWritten(profile_id, ..) | AppMessage::HistoryStepped(profile_id, _)
+            | AppMessage::GoalAdded(profile_id, ..) | AppMessage::GoalRemoved(profile_id, ..) | AppMessage::ProposalsAccepted(profile_id, ..)
+            | AppMessage::RecentActivityLoaded(profile_id, _) | AppMessage::ComparisonLoaded(profile_id, _)
+                if profile_id != self.state.profile().id => {}
+            AppMessage::VisitChangesWritten(_, changes, result) => return self.update_visit_changes_written(changes, result),
+            AppMessage::VisitKindWritten(_, country, visit_kind, result) => self.update_visit_kind_written(country, visit_kind, result),
+            AppMessage::VisitDateWritten(_, country, date, result) => self.update_visit_date_written(country, date, result),
+            AppMessage::WishWritten(_, country, wish, result) => self.update_wish_written(country, wish, result),
+            AppMessage::DescriptionWritten(_, country, description, result) => self.update_description_written(country, description, result),
+            AppMessage::HistoryStepped(_, result) => return self.update_history_stepped(result),
+            AppMessage::RecentActivityLoaded(_, result) => self.update_recent_activity_loaded(result),
+            AppMessage::ErrorBanner(msg) => self.error_banner.update(msg),
+            AppMessage::ProfileSwitcher(msg) => return self.update_profile_switcher_event(msg),
+            AppMessage::ProfileLoaded(result) => return self.update_profile_loaded(result),
+            AppMessage::ComparisonLoaded(_, result) => self.update_comparison_loaded(result),
+            AppMessage::Statistics(msg) => self.statistics.update(msg),
+            AppMessage::Timeline(msg) => self.update_timeline_event(msg),
+            AppMessage::SettingsSaved(result) => self.update_settings_saved(result),
+            AppMessage::SettingsPanel(msg) => return self.update_settings_panel_event(msg),
+            AppMessage::BulkActions(msg) => return self.update_bulk_actions_event(msg),
+            AppMessage::SelectionExported(result) => self.update_selection_exported(result),
+            AppMessage::ReportExport(msg) => return self.update_report_export_event(msg),
+            AppMessage::ReportCreated(result) => self.update_report_created(result),
+            AppMessage::Achievements(msg) => self.update_achievements_event(msg),
+            AppMessage::GoalsPanel(msg) => return self.update_goals_panel_event(msg),
+            AppMessage::GoalAdded(_, goal, result) => self.update_goal_added(goal, result),
+            AppMessage::GoalRemoved(_, id, result) => self.update_goal_removed(id, result),
+            AppMessage::GpsImport(msg) => return self.update_gps_import_event(msg),
+            AppMessage::ProposalsRead(result) => self.update_proposals_read(result),
+            AppMessage::ProposalReview(msg) => return self.update_proposal_review_event(msg),
+            AppMessage::ProposalsAccepted(_, proposals, result) => return self.update_proposals_accepted(proposals, result),
         }
         Command::none()
     }
 
     fn view(&self) -> Element<'_, Self::Message, Renderer<Self::Theme>> {
         let country_list = column!(
+            self.profile_switcher.view(&self.state).map(AppMessage::from),
+            iced::widget::horizontal_rule(0),
+            self.statistics.view(&self.state).map(AppMessage::from),
+            iced::widget::horizontal_rule(0),
             self.country_filter.view().map(AppMessage::from),
             iced::widget::horizontal_rule(0),
-            self.country_list.view().map(AppMessage::from),
+            self.focus_frame(Panel::CountryList, self.country_list.view(&self.state).map(AppMessage::from)),
         )
         .width(iced::Length::Fixed(250.0));
-        row!(
-            country_list,
-            iced::widget::vertical_rule(0),
-            self.view_map(),
-            iced::widget::vertical_rule(0),
-            self.view_country_info(),
+        let toolbar = row!(
+            self.report_export.view().map(AppMessage::from),
+            self.gps_import.view().map(AppMessage::from),
+            iced::widget::horizontal_space(iced::Length::Fill),
+            self.goals_panel.view_toggle().map(AppMessage::from),
+            self.achievements.view_toggle().map(AppMessage::from),
+            self.settings_panel.view_toggle().map(AppMessage::from),
+        )
+        .spacing(5)
+        .padding(5);
+        column!(
+            self.error_banner.view().map(AppMessage::from),
+            self.achievements.view_announcement().map(AppMessage::from),
+            toolbar,
+            row!(
+                country_list,
+                iced::widget::vertical_rule(0),
+                self.view_map(),
+                iced::widget::vertical_rule(0),
+                self.view_country_info(),
+            ),
         ).into()
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        let subs = vec![
-            iced::subscription::events().map(AppMessage::Event)
+        let mut subs = vec![
+            iced::subscription::events_with(|event, status| keymap::reaches_key_bindings(&event, status).then_some(AppMessage::Event(event)))
         ];
+        if self.timeline.is_playing() {
+            subs.push(iced::window::frames().map(|instant| AppMessage::Timeline(TimelineMessage::Frame(instant))));
+        }
         Subscription::batch(subs)
     }
 }
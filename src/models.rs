@@ -1,5 +1,10 @@
+use std::fmt::{Display, Formatter};
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::{Associations, Insertable, Queryable, Selectable};
-use crate::base_data::BaseDataCountry;
+use serde::{Deserialize, Serialize};
+use crate::base_data::{BaseDataCountry, Continent};
+use crate::goals::Goal;
+use crate::search;
 
 #[derive(Insertable, Queryable, Selectable, Debug, Clone)]
 #[diesel(table_name = crate::schema::countries)]
@@ -13,10 +18,12 @@ pub struct Country {
 
 impl Country {
     pub fn matches_filter(&self, filter: &str) -> bool {
-        let filter = filter.to_lowercase();
-        self.name.to_lowercase().contains(&filter)
-            || self.iso2.to_lowercase().contains(&filter)
-            || self.iso3.to_lowercase().contains(&filter)
+        self.search_score(filter).is_some()
+    }
+
+    /// Ranks the country against a search string, see `search::country_score`.
+    pub fn search_score(&self, filter: &str) -> Option<u32> {
+        search::country_score(filter, &self.name, &self.iso2, &self.iso3)
     }
 }
 
@@ -45,11 +52,389 @@ impl From<BaseDataCountry> for NewCountry {
 #[diesel(table_name = crate::schema::country_visits)]
 pub struct CountryVisit {
     pub id: i32,
+    pub profile_id: i32,
     pub country_id: i32,
+    pub kind: String,
+    /// `None` for visits recorded before visit dates were.
+    pub visited_on: Option<NaiveDate>,
+}
+
+impl CountryVisit {
+    /// Kind of the visit, visits of kinds unknown to this version count as plain visits.
+    pub fn kind(&self) -> VisitKind {
+        VisitKind::parse(&self.kind).unwrap_or_default()
+    }
+
+    /// Kind and date of the visit as written to the change log when it is removed, like
+    /// `visited;2024-05-01`, or `lived;` for a visit without a date.
+    pub fn to_log_value(&self) -> String {
+        let date = self.visited_on.map(|date| date.to_string()).unwrap_or_default();
+        format!("{};{}", self.kind, date)
+    }
+
+    /// Kind and date of a removed visit from the change log, `None` for removals logged before
+    /// the change log kept them.
+    pub fn from_log_value(value: &str) -> Option<(&str, Option<NaiveDate>)> {
+        let (kind, date) = value.split_once(';')?;
+        let date = match date {
+            "" => None,
+            date => Some(date.parse().ok()?),
+        };
+        Some((kind, date))
+    }
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::country_visits)]
 pub struct NewCountryVisit {
+    pub profile_id: i32,
     pub country_id: i32,
+    pub kind: String,
+    pub visited_on: Option<NaiveDate>,
+}
+
+/// How much time was spent in a visited country. Transit through an airport is recorded, but
+/// not every statistic counts it as a visit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VisitKind {
+    Lived,
+    ExtendedStay,
+    #[default]
+    Visited,
+    DayTrip,
+    Transit,
+}
+
+impl VisitKind {
+    pub const ALL: [VisitKind; 5] = [
+        VisitKind::Lived,
+        VisitKind::ExtendedStay,
+        VisitKind::Visited,
+        VisitKind::DayTrip,
+        VisitKind::Transit,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VisitKind::Lived => "lived",
+            VisitKind::ExtendedStay => "extended_stay",
+            VisitKind::Visited => "visited",
+            VisitKind::DayTrip => "day_trip",
+            VisitKind::Transit => "transit",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "lived" => Some(VisitKind::Lived),
+            "extended_stay" => Some(VisitKind::ExtendedStay),
+            "visited" => Some(VisitKind::Visited),
+            "day_trip" => Some(VisitKind::DayTrip),
+            "transit" => Some(VisitKind::Transit),
+            _ => None,
+        }
+    }
+}
+
+impl Display for VisitKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VisitKind::Lived => write!(f, "lived"),
+            VisitKind::ExtendedStay => write!(f, "extended stay"),
+            VisitKind::Visited => write!(f, "visited"),
+            VisitKind::DayTrip => write!(f, "day trip"),
+            VisitKind::Transit => write!(f, "transit only"),
+        }
+    }
+}
+
+/// Separate travel log of one person sharing the database.
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq, Eq)]
+#[diesel(table_name = crate::schema::profiles)]
+pub struct Profile {
+    pub id: i32,
+    pub name: String,
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::profiles)]
+pub struct NewProfile {
+    pub name: String,
+}
+
+/// Whether a country the profile has not been to yet is a firm plan or just a wish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WishlistStatus {
+    Planned,
+    Wishlist,
+}
+
+impl WishlistStatus {
+    pub const ALL: [WishlistStatus; 2] = [WishlistStatus::Planned, WishlistStatus::Wishlist];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WishlistStatus::Planned => "planned",
+            WishlistStatus::Wishlist => "wishlist",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "planned" => Some(WishlistStatus::Planned),
+            "wishlist" => Some(WishlistStatus::Wishlist),
+            _ => None,
+        }
+    }
+}
+
+impl Display for WishlistStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A country on the wishlist of a profile. Priority 1 is the most wanted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wish {
+    pub status: WishlistStatus,
+    pub priority: i32,
+    pub target_year: Option<i32>,
+}
+
+impl Wish {
+    pub const PRIORITIES: [i32; 5] = [1, 2, 3, 4, 5];
+    pub const DEFAULT_PRIORITY: i32 = 3;
+
+    pub fn new(status: WishlistStatus) -> Self {
+        Self {
+            status,
+            priority: Wish::DEFAULT_PRIORITY,
+            target_year: None,
+        }
+    }
+
+    /// The wish as written to the change log.
+    pub fn to_log_value(self) -> String {
+        serde_json::to_string(&self).expect("wishes can be written as JSON")
+    }
+
+    pub fn from_log_value(value: &str) -> Option<Self> {
+        serde_json::from_str(value).ok()
+    }
+}
+
+/// Note of a profile about a country, like something to do there.
+#[derive(Queryable, Selectable, Associations, Debug, Clone)]
+#[diesel(belongs_to(Country))]
+#[diesel(table_name = crate::schema::country_notes)]
+pub struct CountryNote {
+    pub country_id: i32,
+    pub note: Option<String>,
+    pub done: Option<bool>,
+}
+
+#[derive(Queryable, Selectable, Associations, Debug, Clone)]
+#[diesel(belongs_to(Country))]
+#[diesel(table_name = crate::schema::wishlist_entries)]
+pub struct WishlistEntry {
+    pub country_id: i32,
+    pub status: String,
+    pub priority: i32,
+    pub target_year: Option<i32>,
+}
+
+impl WishlistEntry {
+    /// The wish stored in this entry, `None` if the status is unknown to this version.
+    pub fn wish(&self) -> Option<Wish> {
+        Some(Wish {
+            status: WishlistStatus::parse(&self.status)?,
+            priority: self.priority,
+            target_year: self.target_year,
+        })
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::wishlist_entries)]
+pub struct NewWishlistEntry {
+    pub profile_id: i32,
+    pub country_id: i32,
+    pub status: String,
+    pub priority: i32,
+    pub target_year: Option<i32>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[diesel(table_name = crate::schema::goals)]
+pub struct GoalEntry {
+    pub id: i32,
+    pub kind: String,
+    pub count: Option<i32>,
+    pub year: Option<i32>,
+    pub continent: Option<String>,
+}
+
+impl GoalEntry {
+    /// The goal stored in this entry, `None` if its kind is unknown to this version or it lacks
+    /// what the kind needs.
+    pub fn goal(&self) -> Option<Goal> {
+        match self.kind.as_str() {
+            "new_countries" => Some(Goal::CountriesInYear {
+                count: usize::try_from(self.count?).ok()?,
+                year: self.year?,
+            }),
+            "continent" => Some(Goal::Continent(Continent::parse(self.continent.as_deref()?)?)),
+            _ => None,
+        }
+    }
+
+    /// The entry as written to the change log, with its id so it can be restored as it was.
+    pub fn to_log_value(&self) -> String {
+        serde_json::to_string(self).expect("goals can be written as JSON")
+    }
+
+    pub fn from_log_value(value: &str) -> Option<Self> {
+        serde_json::from_str(value).ok()
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::goals)]
+pub struct NewGoal {
+    pub profile_id: i32,
+    pub kind: String,
+    pub count: Option<i32>,
+    pub year: Option<i32>,
+    pub continent: Option<String>,
+}
+
+impl NewGoal {
+    pub fn new(profile_id: i32, goal: Goal) -> Self {
+        match goal {
+            Goal::CountriesInYear { count, year } => Self {
+                profile_id,
+                kind: "new_countries".to_string(),
+                count: i32::try_from(count).ok(),
+                year: Some(year),
+                continent: None,
+            },
+            Goal::Continent(continent) => Self {
+                profile_id,
+                kind: "continent".to_string(),
+                count: None,
+                year: None,
+                continent: Some(continent.as_str().to_string()),
+            },
+        }
+    }
+}
+
+/// Kind of change recorded in the change log. Stored as text so new kinds of changes can be
+/// added without migrating old entries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Visit,
+    Unvisit,
+    ImportCountry,
+    Undo,
+    Redo,
+    SetVisitKind,
+    SetVisitDate,
+    SetWish,
+    SetDescription,
+    AddGoal,
+    RemoveGoal,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Visit => "visit",
+            Operation::Unvisit => "unvisit",
+            Operation::ImportCountry => "import_country",
+            Operation::Undo => "undo",
+            Operation::Redo => "redo",
+            Operation::SetVisitKind => "set_visit_kind",
+            Operation::SetVisitDate => "set_visit_date",
+            Operation::SetWish => "set_wish",
+            Operation::SetDescription => "set_description",
+            Operation::AddGoal => "add_goal",
+            Operation::RemoveGoal => "remove_goal",
+        }
+    }
+
+    pub fn parse(operation: &str) -> Option<Self> {
+        match operation {
+            "visit" => Some(Operation::Visit),
+            "unvisit" => Some(Operation::Unvisit),
+            "import_country" => Some(Operation::ImportCountry),
+            "undo" => Some(Operation::Undo),
+            "redo" => Some(Operation::Redo),
+            "set_visit_kind" => Some(Operation::SetVisitKind),
+            "set_visit_date" => Some(Operation::SetVisitDate),
+            "set_wish" => Some(Operation::SetWish),
+            "set_description" => Some(Operation::SetDescription),
+            "add_goal" => Some(Operation::AddGoal),
+            "remove_goal" => Some(Operation::RemoveGoal),
+            _ => None,
+        }
+    }
+
+    /// Whether entries of the operation record a value that is put back by undo and redo.
+    pub fn is_value_change(&self) -> bool {
+        matches!(self, Operation::SetVisitKind | Operation::SetVisitDate | Operation::SetWish | Operation::SetDescription | Operation::AddGoal | Operation::RemoveGoal)
+    }
+}
+
+/// Where a change to the database was made.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeSource {
+    Gui,
+    Cli,
+    Import,
+}
+
+impl ChangeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeSource::Gui => "gui",
+            ChangeSource::Cli => "cli",
+            ChangeSource::Import => "import",
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Associations, Debug, Clone)]
+#[diesel(belongs_to(Country))]
+#[diesel(table_name = crate::schema::change_log)]
+pub struct ChangeLogEntry {
+    pub change_set: i32,
+    pub operation: String,
+    /// `None` for changes that do not concern a country, like goals.
+    pub country_id: Option<i32>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: NaiveDateTime,
+    pub source: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::change_log)]
+pub struct NewChangeLogEntry {
+    pub change_set: i32,
+    pub operation: String,
+    pub country_id: Option<i32>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub status: i32,
+    pub source: String,
+    pub profile_id: i32,
 }
\ No newline at end of file
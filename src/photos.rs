@@ -0,0 +1,115 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use chrono::NaiveDate;
+use exif::{Exif, In, Rational, Tag, Value};
+use crate::importer::ImportError;
+use crate::visit_proposals::GpsPoint;
+
+const PHOTO_EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "tif", "tiff"];
+
+/// Reads the positions of the geotagged JPEG and TIFF photos in the directory and its
+/// subdirectories. Photos that cannot be opened or have no position or readable EXIF data are
+/// skipped, only a directory that cannot be listed fails the scan.
+pub fn read_photo_points(directory: &Path) -> Result<Vec<GpsPoint>, ImportError> {
+    let mut points = vec![];
+    for path in photo_paths(directory)? {
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        let Ok(exif) = exif::Reader::new().read_from_container(&mut BufReader::new(file)) else {
+            continue;
+        };
+        points.extend(photo_point(&exif));
+    }
+    Ok(points)
+}
+
+/// Photos in the directory and its subdirectories. Symbolic links to directories are not
+/// followed, so a link back up the tree cannot make the scan go round in circles.
+fn photo_paths(directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            paths.extend(photo_paths(&path)?);
+        } else if is_photo(&path) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn is_photo(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| PHOTO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+fn photo_point(exif: &Exif) -> Option<GpsPoint> {
+    let latitude = coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef)?;
+    let longitude = coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef)?;
+    let date = [Tag::DateTimeOriginal, Tag::DateTime, Tag::GPSDateStamp].into_iter()
+        .find_map(|tag| ascii(exif, tag).and_then(parse_date));
+    Some(GpsPoint { latitude, longitude, date })
+}
+
+fn coordinate(exif: &Exif, tag: Tag, reference: Tag) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(parts) => degrees(parts, ascii(exif, reference)?),
+        _ => None,
+    }
+}
+
+fn ascii(exif: &Exif, tag: Tag) -> Option<&[u8]> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values.first().map(|value| value.as_slice()),
+        _ => None,
+    }
+}
+
+/// Degrees from EXIF degrees, minutes and seconds, negative to the south and west.
+fn degrees(parts: &[Rational], reference: &[u8]) -> Option<f64> {
+    let [degrees, minutes, seconds] = parts else {
+        return None;
+    };
+    let value = degrees.to_f64() + minutes.to_f64() / 60.0 + seconds.to_f64() / 3600.0;
+    match reference {
+        b"N" | b"E" => Some(value),
+        b"S" | b"W" => Some(-value),
+        _ => None,
+    }.filter(|value| value.is_finite())
+}
+
+/// The day of an EXIF date and time, `YYYY:MM:DD` followed by the local time of the camera.
+fn parse_date(text: &[u8]) -> Option<NaiveDate> {
+    let text = std::str::from_utf8(text.get(..10)?).ok()?;
+    NaiveDate::parse_from_str(text, "%Y:%m:%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use exif::Rational;
+    use crate::photos::{degrees, parse_date};
+
+    #[test]
+    fn coordinates_are_signed_by_hemisphere() {
+        let parts = [Rational::from((47, 1)), Rational::from((33, 1)), Rational::from((3600, 100))];
+        assert_eq!(Some(47.56), degrees(&parts, b"N").map(|value| (value * 100.0).round() / 100.0));
+        assert_eq!(Some(-47.56), degrees(&parts, b"W").map(|value| (value * 100.0).round() / 100.0));
+        assert_eq!(None, degrees(&parts, b""));
+        assert_eq!(None, degrees(&parts[..2], b"N"));
+        assert_eq!(None, degrees(&[Rational::from((47, 0)), Rational::from((0, 1)), Rational::from((0, 1))], b"N"));
+    }
+
+    #[test]
+    fn dates_are_read_without_the_time() {
+        assert_eq!(NaiveDate::from_ymd_opt(2019, 8, 14), parse_date(b"2019:08:14 18:02:11"));
+        assert_eq!(NaiveDate::from_ymd_opt(2019, 8, 14), parse_date(b"2019:08:14"));
+        assert_eq!(None, parse_date(b"    :  :     :  :  "));
+    }
+}
@@ -0,0 +1,177 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use itertools::Itertools;
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+use crate::color_scheme::MapColors;
+use crate::state::AppState;
+use crate::svg_helper::{COUNTRY_POLYGONS, SVG_HEIGT, SVG_WIDTH};
+
+const OCEAN: iced::Color = iced::Color { r: 0.84, g: 0.91, b: 0.96, a: 1.0 };
+const UNVISITED: iced::Color = iced::Color { r: 0.85, g: 0.85, b: 0.85, a: 1.0 };
+const BORDER: iced::Color = iced::Color { r: 0.3, g: 0.3, b: 0.3, a: 1.0 };
+const PROGRESS_HEIGHT: f32 = 6.0;
+/// The last frame stays up this many frames longer, so the finished map can be seen before the
+/// GIF starts over.
+const FINAL_FRAME_HOLD: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReplayFormat {
+    /// A single animated GIF
+    Gif,
+    /// A directory of numbered PNG frames
+    Png,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Image(image::ImageError),
+    FrameSize(u32, u32),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{}", e),
+            ReplayError::Image(e) => write!(f, "{}", e),
+            ReplayError::FrameSize(width, height) => write!(f, "cannot draw frames of {} x {} pixels", width, height),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Io(e) => Some(e),
+            ReplayError::Image(e) => Some(e),
+            ReplayError::FrameSize(_, _) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(value: std::io::Error) -> Self {
+        ReplayError::Io(value)
+    }
+}
+
+impl From<image::ImageError> for ReplayError {
+    fn from(value: image::ImageError) -> Self {
+        ReplayError::Image(value)
+    }
+}
+
+/// Days shown by the frames of a replay: the day before the first visit, when only visits
+/// without a date are on the map, and then every day on which a country was visited. Without
+/// any dated visit there is a single frame of today.
+pub fn frame_dates(visit_dates: impl IntoIterator<Item = NaiveDate>, today: NaiveDate) -> Vec<NaiveDate> {
+    let dates: Vec<NaiveDate> = visit_dates.into_iter().sorted().dedup().collect();
+    match dates.first() {
+        Some(first) => std::iter::once(*first - Duration::days(1)).chain(dates).collect(),
+        None => vec![today],
+    }
+}
+
+/// Draws the map as it was on each of the days, with a bar along the bottom showing how far
+/// the replay has come.
+pub fn render_frames(state: &AppState, dates: &[NaiveDate], width: u32, map_colors: MapColors) -> Result<Vec<RgbaImage>, ReplayError> {
+    let height = (width as f32 * SVG_HEIGT / SVG_WIDTH).round() as u32;
+    dates.iter()
+        .enumerate()
+        .map(|(index, date)| {
+            let progress = (index + 1) as f32 / dates.len() as f32;
+            render_frame(state, *date, progress, width, height, map_colors)
+        })
+        .collect()
+}
+
+fn render_frame(state: &AppState, as_of: NaiveDate, progress: f32, width: u32, height: u32, map_colors: MapColors) -> Result<RgbaImage, ReplayError> {
+    let mut pixmap = Pixmap::new(width, height).ok_or(ReplayError::FrameSize(width, height))?;
+    pixmap.fill(skia_color(OCEAN));
+    let mut paint = Paint { anti_alias: true, ..Paint::default() };
+    let stroke = Stroke { width: 0.5, ..Stroke::default() };
+    for (country, visit_kind) in state.countries_with_visit_kind(Some(as_of)) {
+        let Some(polygons) = COUNTRY_POLYGONS.get(&country.iso2) else {
+            continue;
+        };
+        let fill = if visit_kind.is_some() { map_colors.visited() } else { UNVISITED };
+        for polygon in polygons {
+            let mut builder = PathBuilder::new();
+            for (index, point) in polygon.iter().enumerate() {
+                let (x, y) = (point.0 * width as f32, point.1 * height as f32);
+                if index == 0 {
+                    builder.move_to(x, y);
+                } else {
+                    builder.line_to(x, y);
+                }
+            }
+            builder.close();
+            let Some(path) = builder.finish() else {
+                continue;
+            };
+            paint.set_color(skia_color(fill));
+            pixmap.fill_path(&path, &paint, FillRule::EvenOdd, Transform::identity(), None);
+            paint.set_color(skia_color(BORDER));
+            pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+    if let Some(bar) = Rect::from_xywh(0.0, height as f32 - PROGRESS_HEIGHT, width as f32 * progress, PROGRESS_HEIGHT) {
+        paint.set_color(skia_color(map_colors.visited()));
+        pixmap.fill_rect(bar, &paint, Transform::identity(), None);
+    }
+    RgbaImage::from_raw(width, height, pixmap.take()).ok_or(ReplayError::FrameSize(width, height))
+}
+
+fn skia_color(color: iced::Color) -> tiny_skia::Color {
+    let [r, g, b, a] = color.into_rgba8();
+    tiny_skia::Color::from_rgba8(r, g, b, a)
+}
+
+/// Writes the frames as an endlessly looping GIF, or as numbered PNG files into the directory
+/// at `path`. Returns the written file or directory.
+pub fn export(frames: Vec<RgbaImage>, path: &Path, format: ReplayFormat, frame_ms: u32) -> Result<PathBuf, ReplayError> {
+    match format {
+        ReplayFormat::Gif => {
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(File::create(path)?), 10);
+            encoder.set_repeat(Repeat::Infinite)?;
+            let count = frames.len();
+            let frames = frames.into_iter()
+                .enumerate()
+                .map(|(index, frame)| {
+                    let hold = if index + 1 == count { FINAL_FRAME_HOLD } else { 1 };
+                    Frame::from_parts(frame, 0, 0, Delay::from_numer_denom_ms(frame_ms * hold, 1))
+                });
+            encoder.encode_frames(frames)?;
+        }
+        ReplayFormat::Png => {
+            std::fs::create_dir_all(path)?;
+            for (index, frame) in frames.iter().enumerate() {
+                frame.save(path.join(format!("frame_{:04}.png", index + 1)))?;
+            }
+        }
+    }
+    Ok(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use crate::replay::frame_dates;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn frames_start_before_the_first_visit() {
+        let visits = [date(2020, 5, 1), date(2019, 1, 1), date(2020, 5, 1)];
+        assert_eq!(vec![date(2018, 12, 31), date(2019, 1, 1), date(2020, 5, 1)], frame_dates(visits, date(2026, 1, 1)));
+        assert_eq!(vec![date(2026, 1, 1)], frame_dates([], date(2026, 1, 1)));
+    }
+}
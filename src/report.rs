@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::Datelike;
+use iced::Color;
+use crate::base_data::Continent;
+use crate::color_scheme::MapColors;
+use crate::database;
+use crate::database::DatabaseError;
+use crate::flag_helper::flag_png;
+use crate::models::{Country, CountryNote, Profile};
+use crate::state::AppState;
+use crate::svg_helper::{COUNTRY_POLYGONS, SVG_HEIGT, SVG_WIDTH};
+
+const UNVISITED: Color = Color { r: 0.85, g: 0.85, b: 0.85, a: 1.0 };
+const OCEAN: Color = Color { r: 0.84, g: 0.91, b: 0.96, a: 1.0 };
+
+#[derive(Debug)]
+pub enum ReportError {
+    Database(DatabaseError),
+    Io(std::io::Error),
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::Database(e) => write!(f, "{}", e),
+            ReportError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReportError::Database(e) => Some(e),
+            ReportError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<DatabaseError> for ReportError {
+    fn from(value: DatabaseError) -> Self {
+        ReportError::Database(value)
+    }
+}
+
+impl From<diesel::result::Error> for ReportError {
+    fn from(value: diesel::result::Error) -> Self {
+        ReportError::Database(DatabaseError::Diesel(value))
+    }
+}
+
+impl From<std::io::Error> for ReportError {
+    fn from(value: std::io::Error) -> Self {
+        ReportError::Io(value)
+    }
+}
+
+/// Writes the report of the profile to a single HTML file that needs nothing else to be viewed,
+/// limited to the countries visited in `year` if given.
+pub fn write_report(path: &Path, profile: &Profile, year: Option<i32>, map_colors: MapColors) -> Result<(), ReportError> {
+    let mut connection = database::connection()?;
+    let state = AppState::load(&mut connection, profile.clone())?;
+    let notes = database::all_notes(&mut connection, profile.id)?;
+    fs::write(path, build_report(&state, &notes, year, map_colors))?;
+    Ok(())
+}
+
+pub async fn create_report(path: PathBuf, profile: Profile, year: Option<i32>, map_colors: MapColors) -> Result<PathBuf, ReportError> {
+    write_report(&path, &profile, year, map_colors)?;
+    Ok(path)
+}
+
+pub fn build_report(state: &AppState, notes: &[CountryNote], year: Option<i32>, map_colors: MapColors) -> String {
+    let mut visited: Vec<&Country> = state.countries()
+        .filter(|country| state.is_visited(country))
+        .filter(|country| year.is_none() || state.visited_on(country).map(|date| date.year()) == year)
+        .collect();
+    visited.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut notes_by_country: HashMap<i32, Vec<&CountryNote>> = HashMap::new();
+    for note in notes {
+        notes_by_country.entry(note.country_id).or_default().push(note);
+    }
+    let title = match year {
+        Some(year) => format!("Travels of {} in {}", state.profile().name, year),
+        None => format!("Travels of {}", state.profile().name),
+    };
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape(&title));
+    html.push_str(STYLE);
+    html.push_str("</head>\n<body>\n");
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&title));
+    html.push_str(&map_svg(state, &visited, map_colors));
+    html.push_str(&totals(state, &visited, year));
+    html.push_str("<h2>Visited countries</h2>\n<table class=\"countries\">\n");
+    for country in &visited {
+        let flag = flag_png(&country.iso2)
+            .map(|png| format!("<img src=\"data:image/png;base64,{}\" alt=\"\">", base64(png)))
+            .unwrap_or_default();
+        let date = state.visited_on(country).map(|date| date.to_string()).unwrap_or_default();
+        let country_notes = notes_by_country.remove(&country.id).unwrap_or_default();
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            flag,
+            escape(&country.name),
+            date,
+            notes_list(&country_notes),
+        );
+    }
+    html.push_str("</table>\n");
+    let mut other_notes: Vec<(&Country, Vec<&CountryNote>)> = state.countries()
+        .filter_map(|country| notes_by_country.remove(&country.id).map(|notes| (country, notes)))
+        .collect();
+    if !other_notes.is_empty() {
+        other_notes.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+        html.push_str("<h2>Notes on other countries</h2>\n<table class=\"countries\">\n");
+        for (country, notes) in other_notes {
+            let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", escape(&country.name), notes_list(&notes));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const STYLE: &str = "<style>
+body { font-family: sans-serif; margin: 2em auto; max-width: 1000px; color: #222; }
+svg { width: 100%; height: auto; }
+table { border-collapse: collapse; }
+td, th { padding: 4px 10px; text-align: left; vertical-align: top; }
+.countries tr:nth-child(even) { background: #f3f3f3; }
+.countries img { width: 32px; }
+ul { margin: 0; padding-left: 1.2em; }
+.done { text-decoration: line-through; color: #777; }
+</style>
+";
+
+fn map_svg(state: &AppState, visited: &[&Country], map_colors: MapColors) -> String {
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\">\n<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        SVG_WIDTH, SVG_HEIGT, hex(OCEAN),
+    );
+    for country in state.countries() {
+        let Some(polygons) = COUNTRY_POLYGONS.get(&country.iso2) else {
+            continue;
+        };
+        let fill = if visited.iter().any(|visited| visited.id == country.id) {
+            map_colors.visited()
+        } else {
+            UNVISITED
+        };
+        let mut path = String::new();
+        for polygon in polygons {
+            for (index, point) in polygon.iter().enumerate() {
+                let command = if index == 0 { 'M' } else { 'L' };
+                let _ = write!(path, "{}{:.1} {:.1} ", command, point.0 * SVG_WIDTH, point.1 * SVG_HEIGT);
+            }
+            path.push_str("Z ");
+        }
+        let _ = writeln!(
+            svg,
+            "<path d=\"{}\" fill=\"{}\" fill-rule=\"evenodd\" stroke=\"#4d4d4d\" stroke-width=\"0.5\"><title>{}</title></path>",
+            path.trim_end(), hex(fill), escape(&country.name),
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn totals(state: &AppState, visited: &[&Country], year: Option<i32>) -> String {
+    let total = state.country_count().max(1);
+    let summary = match year {
+        Some(year) => format!("{} countries visited in {}, {} of {} in total", visited.len(), year, state.visited_count(true), total),
+        None => format!(
+            "{} of {} countries visited ({:.1}%)",
+            visited.len(), total, visited.len() as f32 * 100.0 / total as f32,
+        ),
+    };
+    let mut html = format!("<h2>Totals</h2>\n<p>{}</p>\n<table>\n<tr><th>Continent</th><th>Visited</th><th>Countries</th></tr>\n", summary);
+    for continent in Continent::ALL {
+        let on_continent = |country: &&Country| Continent::of_iso2(&country.iso2) == Some(continent);
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            continent,
+            visited.iter().copied().filter(on_continent).count(),
+            state.countries().filter(on_continent).count(),
+        );
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn notes_list(notes: &[&CountryNote]) -> String {
+    let items: Vec<String> = notes.iter()
+        .filter_map(|note| {
+            let text = note.note.as_deref()?;
+            let class = if note.done == Some(true) { " class=\"done\"" } else { "" };
+            Some(format!("<li{}>{}</li>", class, escape(text)))
+        })
+        .collect();
+    if items.is_empty() {
+        String::new()
+    } else {
+        format!("<ul>{}</ul>", items.concat())
+    }
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk.iter().enumerate().fold(0u32, |triple, (index, byte)| triple | (*byte as u32) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(triple >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::{base64, escape};
+
+    #[test]
+    fn base64_is_padded() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+    }
+
+    #[test]
+    fn notes_are_escaped() {
+        assert_eq!("Fish &amp; chips &lt;3", escape("Fish & chips <3"));
+    }
+}
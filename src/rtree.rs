@@ -0,0 +1,153 @@
+use crate::svg_helper::{Point, Polygon};
+
+/// Entries per node. Small nodes keep the boxes tight, which matters more than the depth of the
+/// tree for a few thousand border polygons.
+const NODE_SIZE: usize = 8;
+
+/// Axis-aligned box, in the same coordinates as the points it bounds.
+#[derive(Clone)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn of_polygon(polygon: &Polygon) -> Option<BoundingBox> {
+        let first = polygon.iter().next()?;
+        Some(polygon.iter().fold(BoundingBox { min: first.clone(), max: first.clone() }, |bounds, point| {
+            bounds.union(&BoundingBox { min: point.clone(), max: point.clone() })
+        }))
+    }
+
+    /// The box around `point` reaching `margin` out in each direction.
+    pub fn around(point: &Point, margin: &Point) -> BoundingBox {
+        BoundingBox {
+            min: Point(point.0 - margin.0, point.1 - margin.1),
+            max: Point(point.0 + margin.0, point.1 + margin.1),
+        }
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Point(self.min.0.min(other.min.0), self.min.1.min(other.min.1)),
+            max: Point(self.max.0.max(other.max.0), self.max.1.max(other.max.1)),
+        }
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.0 <= other.max.0 && other.min.0 <= self.max.0 && self.min.1 <= other.max.1 && other.min.1 <= self.max.1
+    }
+
+    pub fn area(&self) -> f32 {
+        (self.max.0 - self.min.0) * (self.max.1 - self.min.1)
+    }
+
+    fn center(&self) -> Point {
+        Point((self.min.0 + self.max.0) / 2.0, (self.min.1 + self.max.1) / 2.0)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Child {
+    Item(usize),
+    Node(usize),
+}
+
+struct Node {
+    children: Vec<(BoundingBox, Child)>,
+}
+
+/// Static R-tree over the bounding boxes of its items, built once with sort-tile-recursive
+/// packing.
+pub struct RTree<T> {
+    items: Vec<T>,
+    nodes: Vec<Node>,
+    root: Option<(BoundingBox, Child)>,
+}
+
+impl<T> RTree<T> {
+    pub fn new(entries: Vec<(BoundingBox, T)>) -> Self {
+        let mut items = Vec::with_capacity(entries.len());
+        let mut level: Vec<(BoundingBox, Child)> = entries.into_iter()
+            .map(|(bounds, item)| {
+                items.push(item);
+                (bounds, Child::Item(items.len() - 1))
+            })
+            .collect();
+        let mut nodes = vec![];
+        while level.len() > 1 {
+            level = pack(level).into_iter()
+                .map(|children| {
+                    let bounds = children.iter()
+                        .map(|(bounds, _)| bounds.clone())
+                        .reduce(|a, b| a.union(&b))
+                        .expect("packed nodes are never empty");
+                    nodes.push(Node { children });
+                    (bounds, Child::Node(nodes.len() - 1))
+                })
+                .collect();
+        }
+        Self {
+            items,
+            nodes,
+            root: level.pop(),
+        }
+    }
+
+    /// Items whose bounding box intersects `area`.
+    pub fn search(&self, area: &BoundingBox) -> Vec<&T> {
+        let mut found = vec![];
+        let mut pending: Vec<&(BoundingBox, Child)> = self.root.iter().collect();
+        while let Some((bounds, child)) = pending.pop() {
+            if !bounds.intersects(area) {
+                continue;
+            }
+            match child {
+                Child::Item(index) => found.push(&self.items[*index]),
+                Child::Node(index) => pending.extend(self.nodes[*index].children.iter()),
+            }
+        }
+        found
+    }
+}
+
+/// Groups the entries into nodes of at most `NODE_SIZE` neighbouring entries: sorted by x into
+/// vertical slices, and each slice sorted by y before it is cut into nodes.
+fn pack(mut entries: Vec<(BoundingBox, Child)>) -> Vec<Vec<(BoundingBox, Child)>> {
+    let node_count = entries.len().div_ceil(NODE_SIZE);
+    let slice_count = (node_count as f64).sqrt().ceil() as usize;
+    let slice_size = slice_count * NODE_SIZE;
+    entries.sort_by(|(a, _), (b, _)| a.center().0.total_cmp(&b.center().0));
+    let mut nodes = Vec::with_capacity(node_count);
+    for slice in entries.chunks_mut(slice_size) {
+        slice.sort_by(|(a, _), (b, _)| a.center().1.total_cmp(&b.center().1));
+        nodes.extend(slice.chunks(NODE_SIZE).map(<[_]>::to_vec));
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rtree::{BoundingBox, RTree};
+    use crate::svg_helper::Point;
+
+    #[test]
+    fn search_finds_exactly_the_intersecting_boxes() {
+        let entries: Vec<(BoundingBox, usize)> = (0..100)
+            .map(|index| {
+                let (x, y) = ((index % 10) as f32, (index / 10) as f32);
+                (BoundingBox { min: Point(x, y), max: Point(x + 0.5, y + 0.5) }, index)
+            })
+            .collect();
+        let tree = RTree::new(entries);
+        let mut found: Vec<usize> = tree.search(&BoundingBox::around(&Point(3.4, 7.4), &Point(0.0, 0.0)))
+            .into_iter()
+            .copied()
+            .collect();
+        assert_eq!(vec![73], found);
+        found = tree.search(&BoundingBox { min: Point(1.2, 1.2), max: Point(2.2, 2.2) }).into_iter().copied().collect();
+        found.sort();
+        assert_eq!(vec![11, 12, 21, 22], found);
+        assert!(tree.search(&BoundingBox::around(&Point(3.7, 7.7), &Point(0.1, 0.1))).is_empty());
+    }
+}
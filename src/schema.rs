@@ -1,5 +1,20 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    change_log (id) {
+        id -> Integer,
+        change_set -> Integer,
+        operation -> Text,
+        country_id -> Nullable<Integer>,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+        status -> Integer,
+        changed_at -> Timestamp,
+        source -> Text,
+        profile_id -> Integer,
+    }
+}
+
 diesel::table! {
     countries (id) {
         id -> Integer,
@@ -16,21 +31,63 @@ diesel::table! {
         country_id -> Integer,
         note -> Nullable<Text>,
         done -> Nullable<Bool>,
+        profile_id -> Integer,
     }
 }
 
 diesel::table! {
     country_visits (id) {
         id -> Integer,
+        profile_id -> Integer,
+        country_id -> Integer,
+        kind -> Text,
+        visited_on -> Nullable<Date>,
+    }
+}
+
+diesel::table! {
+    goals (id) {
+        id -> Integer,
+        profile_id -> Integer,
+        kind -> Text,
+        count -> Nullable<Integer>,
+        year -> Nullable<Integer>,
+        continent -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    profiles (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    wishlist_entries (id) {
+        id -> Integer,
+        profile_id -> Integer,
         country_id -> Integer,
+        status -> Text,
+        priority -> Integer,
+        target_year -> Nullable<Integer>,
     }
 }
 
+diesel::joinable!(change_log -> countries (country_id));
 diesel::joinable!(country_notes -> countries (country_id));
 diesel::joinable!(country_visits -> countries (country_id));
+diesel::joinable!(country_visits -> profiles (profile_id));
+diesel::joinable!(goals -> profiles (profile_id));
+diesel::joinable!(wishlist_entries -> countries (country_id));
+diesel::joinable!(wishlist_entries -> profiles (profile_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    change_log,
     countries,
     country_notes,
     country_visits,
+    goals,
+    profiles,
+    wishlist_entries,
 );
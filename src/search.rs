@@ -0,0 +1,128 @@
+use deunicode::deunicode;
+use crate::base_data::BaseDataCountry;
+
+/// Lowercases the text and strips diacritics, so "Côte d'Ivoire" and "cote d'ivoire" compare equal.
+pub fn fold(text: &str) -> String {
+    deunicode(text).to_lowercase()
+}
+
+/// Ranks how well `filter` matches the best of `candidates`. Lower scores are better matches,
+/// `None` means no candidate matches at all, not even with a typo.
+pub fn match_score<'a>(filter: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<u32> {
+    let filter = fold(filter.trim());
+    if filter.is_empty() {
+        return Some(0);
+    }
+    candidates.into_iter()
+        .filter_map(|candidate| candidate_score(&filter, &fold(candidate)))
+        .min()
+}
+
+/// Ranks a country against a search string by its name, ISO codes and the aliases and former
+/// names from the base data. Lower is better, `None` means no match.
+pub fn country_score(filter: &str, name: &str, iso2: &str, iso3: &str) -> Option<u32> {
+    let aliases = BaseDataCountry::by_iso2(iso2)
+        .map(|base_data| base_data.aliases.as_slice())
+        .unwrap_or_default();
+    match_score(filter, [name, iso2, iso3].into_iter().chain(aliases.iter().map(String::as_str)))
+}
+
+fn candidate_score(filter: &str, candidate: &str) -> Option<u32> {
+    if candidate == filter {
+        Some(0)
+    } else if candidate.starts_with(filter) {
+        Some(1)
+    } else if words(candidate).any(|word| word.starts_with(filter)) {
+        Some(2)
+    } else if candidate.contains(filter) {
+        Some(3)
+    } else {
+        typo_distance(filter, candidate).map(|distance| 3 + distance as u32)
+    }
+}
+
+fn typo_distance(filter: &str, candidate: &str) -> Option<usize> {
+    let allowed = allowed_typos(filter);
+    if allowed == 0 {
+        return None;
+    }
+    let filter_length = filter.chars().count();
+    std::iter::once(candidate)
+        .chain(words(candidate))
+        .flat_map(|word| {
+            (filter_length.saturating_sub(1)..=filter_length + 1)
+                .map(move |length| word.chars().take(length).collect::<String>())
+        })
+        .map(|prefix| strsim::damerau_levenshtein(filter, &prefix))
+        .filter(|distance| *distance <= allowed)
+        .min()
+}
+
+fn allowed_typos(filter: &str) -> usize {
+    match filter.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{fold, match_score};
+
+    #[test]
+    fn folding_removes_diacritics() {
+        assert_eq!("cote d'ivoire", fold("Côte d'Ivoire"));
+        assert_eq!("turkiye", fold("Türkiye"));
+        assert_eq!("aland islands", fold("Åland Islands"));
+    }
+
+    #[test]
+    fn matches_without_diacritics() {
+        assert_eq!(Some(0), match_score("Cote d'Ivoire", ["Côte d'Ivoire"]));
+        assert_eq!(Some(1), match_score("reun", ["Réunion"]));
+    }
+
+    #[test]
+    fn better_matches_get_lower_scores() {
+        let exact = match_score("niger", ["Niger"]).unwrap();
+        let prefix = match_score("niger", ["Nigeria"]).unwrap();
+        let word_prefix = match_score("guinea", ["Papua New Guinea"]).unwrap();
+        let contains = match_score("land", ["Finland"]).unwrap();
+        let typo = match_score("germny", ["Germany"]).unwrap();
+        assert!(exact < prefix);
+        assert!(prefix < word_prefix);
+        assert!(word_prefix < contains);
+        assert!(contains < typo);
+    }
+
+    #[test]
+    fn tolerates_typos() {
+        assert!(match_score("germny", ["Germany"]).is_some());
+        assert!(match_score("frnace", ["France"]).is_some());
+        assert!(match_score("switzerlnd", ["Switzerland"]).is_some());
+        assert!(match_score("zealnd", ["New Zealand"]).is_some());
+    }
+
+    #[test]
+    fn short_filters_need_exact_substrings() {
+        assert_eq!(None, match_score("xyz", ["Germany"]));
+        assert_eq!(None, match_score("grm", ["Germany"]));
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert_eq!(Some(0), match_score("", ["Germany"]));
+        assert_eq!(Some(0), match_score("  ", ["Germany"]));
+    }
+
+    #[test]
+    fn best_candidate_wins() {
+        assert_eq!(Some(0), match_score("Holland", ["Netherlands", "NL", "NLD", "Holland"]));
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use homedir::{get_my_home, GetHomeError};
+use serde::{Deserialize, Serialize};
+use crate::color_scheme::MapColors;
+use crate::keymap::KeyBindings;
+use crate::list_options::{CountrySort, VisitFilter};
+
+const SETTINGS_FILE: &str = "settings.json";
+pub const MIN_WINDOW_SIZE: (u32, u32) = (400, 300);
+const MAX_WINDOW_SIZE: (u32, u32) = (16384, 16384);
+
+/// User preferences that survive restarts, stored as JSON next to the default database.
+/// Unknown keys are rejected, so typos in a hand-edited file do not go unnoticed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub window: WindowSettings,
+    pub theme: AppTheme,
+    pub map_colors: MapColors,
+    /// Database used when none is given on the command line or in `DATABASE_URL`.
+    pub database_path: Option<String>,
+    pub filters: FilterSettings,
+    /// Keys of the actions the user rebound, see `keymap::Action` for their names.
+    pub key_bindings: KeyBindings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowSettings {
+    pub width: u32,
+    pub height: u32,
+    /// Position of the window on the screen, `None` lets the platform choose.
+    pub position: Option<(i32, i32)>,
+}
+
+impl WindowSettings {
+    /// Records a new window size. Sizes of zero, as reported for a minimized window, are
+    /// ignored.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.width = width;
+            self.height = height;
+        }
+    }
+
+    /// Brings a size outside of what the window can have back into range.
+    fn clamp_size(&mut self) {
+        self.width = self.width.clamp(MIN_WINDOW_SIZE.0, MAX_WINDOW_SIZE.0);
+        self.height = self.height.clamp(MIN_WINDOW_SIZE.1, MAX_WINDOW_SIZE.1);
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 700,
+            position: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppTheme {
+    #[default]
+    Light,
+    Dark,
+    /// Follows the light or dark preference of the desktop.
+    System,
+}
+
+impl AppTheme {
+    pub const ALL: [AppTheme; 3] = [AppTheme::Light, AppTheme::Dark, AppTheme::System];
+
+    /// The iced theme to render with, for `System` this asks the desktop and should not be
+    /// called on every frame.
+    pub fn iced_theme(&self) -> iced::Theme {
+        match self {
+            AppTheme::Light => iced::Theme::Light,
+            AppTheme::Dark => iced::Theme::Dark,
+            AppTheme::System if system_prefers_dark() => iced::Theme::Dark,
+            AppTheme::System => iced::Theme::Light,
+        }
+    }
+}
+
+/// Asks the desktop whether it prefers dark applications, anything unexpected counts as light.
+fn system_prefers_dark() -> bool {
+    if let Ok(gtk_theme) = std::env::var("GTK_THEME") {
+        return gtk_theme.to_lowercase().contains("dark");
+    }
+    let (program, args, dark_marker): (&str, &[&str], &str) = if cfg!(target_os = "macos") {
+        ("defaults", &["read", "-g", "AppleInterfaceStyle"], "dark")
+    } else if cfg!(windows) {
+        ("reg", &["query", r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize", "/v", "AppsUseLightTheme"], "0x0")
+    } else {
+        ("gsettings", &["get", "org.gnome.desktop.interface", "color-scheme"], "dark")
+    };
+    Command::new(program).args(args).output().ok()
+        .filter(|output| output.status.success())
+        .is_some_and(|output| String::from_utf8_lossy(&output.stdout).to_lowercase().contains(dark_marker))
+}
+
+impl Display for AppTheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppTheme::Light => write!(f, "light"),
+            AppTheme::Dark => write!(f, "dark"),
+            AppTheme::System => write!(f, "system"),
+        }
+    }
+}
+
+/// Filters and selection of the country list as they were when the program was closed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterSettings {
+    pub search: String,
+    /// Also reads `only_visited` as written before the list could show only unvisited countries.
+    #[serde(alias = "only_visited")]
+    pub visited: VisitFilter,
+    pub only_wishlist: bool,
+    pub sort: CountrySort,
+    pub group_by_continent: bool,
+    /// ISO2 code of the selected country.
+    pub selected_country: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    HomeDirectory(GetHomeError),
+    MissingHomeDirectory,
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Invalid(Vec<String>),
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::HomeDirectory(e) => write!(f, "cannot determine home directory: {}", e),
+            SettingsError::MissingHomeDirectory => write!(f, "there is no home directory"),
+            SettingsError::Io(e) => write!(f, "{}", e),
+            SettingsError::Json(e) => write!(f, "invalid settings file: {}", e),
+            SettingsError::Invalid(problems) => write!(f, "invalid settings file: {}", problems.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SettingsError::HomeDirectory(e) => Some(e),
+            SettingsError::Io(e) => Some(e),
+            SettingsError::Json(e) => Some(e),
+            SettingsError::MissingHomeDirectory | SettingsError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SettingsError {
+    fn from(value: std::io::Error) -> Self {
+        SettingsError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SettingsError {
+    fn from(value: serde_json::Error) -> Self {
+        SettingsError::Json(value)
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, a missing file gives the default settings.
+    pub fn load() -> Result<Self, SettingsError> {
+        let path = settings_path()?;
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+        Settings::from_json(&fs::read_to_string(path)?)
+    }
+
+    fn from_json(json: &str) -> Result<Self, SettingsError> {
+        let mut settings: Settings = serde_json::from_str(json)?;
+        settings.window.clamp_size();
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Checks the values that are well-formed JSON but still make no sense. The window size is
+    /// not checked, it is clamped on reading so that one odd size does not cost all settings.
+    fn validate(&self) -> Result<(), SettingsError> {
+        let mut problems = vec![];
+        if self.database_path.as_deref().is_some_and(|path| path.trim().is_empty()) {
+            problems.push("database path is empty".to_string());
+        }
+        if self.filters.selected_country.as_deref().is_some_and(|iso2| iso2.len() != 2) {
+            problems.push("selected country is not an ISO2 code".to_string());
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(SettingsError::Invalid(problems))
+        }
+    }
+
+    pub fn write(&self) -> Result<(), SettingsError> {
+        let path = settings_path()?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub async fn save(self) -> Result<(), SettingsError> {
+        self.write()
+    }
+}
+
+fn settings_path() -> Result<PathBuf, SettingsError> {
+    let home = get_my_home()
+        .map_err(SettingsError::HomeDirectory)?
+        .ok_or(SettingsError::MissingHomeDirectory)?;
+    Ok(home.join(".country_logger").join(SETTINGS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color_scheme::ColorScheme;
+    use crate::keymap::Action;
+    use crate::list_options::{CountrySort, VisitFilter};
+    use crate::settings::{AppTheme, Settings, SettingsError, WindowSettings};
+
+    #[test]
+    fn missing_keys_get_defaults() {
+        let settings = Settings::from_json(r#"{"theme": "dark", "map_colors": {"scheme": "continent"}}"#).unwrap();
+        assert_eq!(AppTheme::Dark, settings.theme);
+        assert_eq!(ColorScheme::Continent, settings.map_colors.scheme);
+        assert_eq!(1200, settings.window.width);
+        assert_eq!(None, settings.database_path);
+    }
+
+    #[test]
+    fn saved_settings_can_be_read_again() {
+        let mut settings = Settings::default();
+        settings.window.position = Some((20, -5));
+        settings.filters.search = "ger".to_string();
+        settings.filters.selected_country = Some("DE".to_string());
+        let json = serde_json::to_string_pretty(&settings).unwrap();
+        assert_eq!(settings, Settings::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn key_bindings_are_read() {
+        let settings = Settings::from_json(r#"{"key_bindings": {"toggle_visited": ["V", "Ctrl+Enter"]}}"#).unwrap();
+        let keys: Vec<String> = settings.key_bindings.keys(Action::ToggleVisited).iter().map(ToString::to_string).collect();
+        assert_eq!(vec!["V", "Ctrl+Enter"], keys);
+        assert!(matches!(Settings::from_json(r#"{"key_bindings": {"undo": ["Hyper+Z"]}}"#), Err(SettingsError::Json(_))));
+    }
+
+    #[test]
+    fn old_visited_filter_is_still_read() {
+        let settings = Settings::from_json(r#"{"filters": {"only_visited": true, "search": "ger"}}"#).unwrap();
+        assert_eq!(VisitFilter::Visited, settings.filters.visited);
+        assert_eq!(CountrySort::Name, settings.filters.sort);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let result = Settings::from_json(r#"{"them": "dark"}"#);
+        assert!(matches!(result, Err(SettingsError::Json(_))));
+    }
+
+    #[test]
+    fn system_theme_is_stored_by_name() {
+        let settings = Settings::from_json(r#"{"theme": "system"}"#).unwrap();
+        assert_eq!(AppTheme::System, settings.theme);
+        assert!(serde_json::to_string(&settings).unwrap().contains(r#""theme":"system""#));
+    }
+
+    #[test]
+    fn unknown_values_are_rejected() {
+        let result = Settings::from_json(r#"{"theme": "purple"}"#);
+        assert!(matches!(result, Err(SettingsError::Json(_))));
+    }
+
+    #[test]
+    fn nonsensical_values_are_rejected() {
+        let result = Settings::from_json(r#"{"database_path": " ", "filters": {"selected_country": "DEU"}}"#);
+        let Err(SettingsError::Invalid(problems)) = result else {
+            panic!("settings should be invalid");
+        };
+        assert_eq!(2, problems.len());
+    }
+
+    #[test]
+    fn window_size_out_of_range_is_clamped() {
+        let settings = Settings::from_json(r#"{"window": {"width": 0, "height": 100000}, "theme": "dark"}"#).unwrap();
+        assert_eq!((400, 16384), (settings.window.width, settings.window.height));
+        assert_eq!(AppTheme::Dark, settings.theme);
+    }
+
+    #[test]
+    fn minimized_window_keeps_its_size() {
+        let mut window = WindowSettings::default();
+        window.resize(0, 0);
+        assert_eq!((1200, 700), (window.width, window.height));
+        window.resize(800, 600);
+        assert_eq!((800, 600), (window.width, window.height));
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use chrono::{Local, NaiveDate, NaiveDateTime, Utc};
+use diesel::SqliteConnection;
+use crate::compare::{ComparisonSide, TravelLog};
+use crate::database;
+use crate::database::{ChangeSet, DatabaseError};
+use crate::goals::Goal;
+use crate::models::{ChangeLogEntry, ChangeSource, Country, Profile, VisitKind, Wish};
+
+/// In-memory copy of the countries and their visit status in the active profile. It is loaded
+/// once on startup or when switching profiles, and kept in sync with the database by writing
+/// every change through to it.
+#[derive(Debug)]
+pub struct AppState {
+    profile: Profile,
+    profiles: Vec<Profile>,
+    countries: Vec<Country>,
+    visited: HashMap<i32, VisitKind>,
+    wishes: HashMap<i32, Wish>,
+    last_visited: HashMap<i32, NaiveDateTime>,
+    visit_dates: HashMap<i32, NaiveDate>,
+    visit_counts: HashMap<i32, usize>,
+    /// Visit rows of every profile, per country.
+    visit_rows: HashMap<i32, usize>,
+    /// Goals of the profile by id, in the order they were set.
+    goals: Vec<(i32, Goal)>,
+    compared_with: Option<TravelLog>,
+}
+
+impl AppState {
+    pub fn load(connection: &mut SqliteConnection, profile: Profile) -> Result<Self, DatabaseError> {
+        let profiles = database::all_profiles(connection)?;
+        let mut countries = vec![];
+        let mut visited = HashMap::new();
+        for (country, visit_kind) in database::all_countries_with_visit_kind(connection, profile.id)? {
+            if let Some(visit_kind) = visit_kind {
+                visited.insert(country.id, visit_kind);
+            }
+            countries.push(country);
+        }
+        let wishes = database::all_wishes(connection, profile.id)?.into_iter()
+            .filter_map(|entry| entry.wish().map(|wish| (entry.country_id, wish)))
+            .collect();
+        let last_visited = database::last_visit_times(connection, profile.id)?;
+        let visit_dates = database::visit_dates(connection, profile.id)?;
+        let visit_counts = database::visit_counts(connection, profile.id)?;
+        let visit_rows = database::visit_row_counts(connection)?;
+        let goals = database::all_goals(connection, profile.id)?.into_iter()
+            .filter_map(|entry| entry.goal().map(|goal| (entry.id, goal)))
+            .collect();
+        Ok(Self {
+            profile,
+            profiles,
+            countries,
+            visited,
+            wishes,
+            last_visited,
+            visit_dates,
+            visit_counts,
+            visit_rows,
+            goals,
+            compared_with: None,
+        })
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn countries(&self) -> impl Iterator<Item = &Country> {
+        self.countries.iter()
+    }
+
+    pub fn countries_with_visit_status(&self) -> impl Iterator<Item = (&Country, bool)> {
+        self.countries.iter().map(|country| (country, self.is_visited(country)))
+    }
+
+    /// Every country with its visit kind as of the given day, see `visit_kind_as_of`.
+    pub fn countries_with_visit_kind(&self, as_of: Option<NaiveDate>) -> impl Iterator<Item = (&Country, Option<VisitKind>)> {
+        self.countries.iter().map(move |country| (country, self.visit_kind_as_of(country, as_of)))
+    }
+
+    pub fn is_visited(&self, country: &Country) -> bool {
+        self.visited.contains_key(&country.id)
+    }
+
+    pub fn visit_kind(&self, country: &Country) -> Option<VisitKind> {
+        self.visited.get(&country.id).copied()
+    }
+
+    pub fn set_visit_kind(&mut self, country: &Country, visit_kind: VisitKind) {
+        if let Some(kind) = self.visited.get_mut(&country.id) {
+            *kind = visit_kind;
+        }
+    }
+
+    /// When the country was last marked as visited, `None` if it is not visited or was visited
+    /// before changes were recorded.
+    pub fn last_visited(&self, country: &Country) -> Option<NaiveDateTime> {
+        self.visited.get(&country.id)
+            .and(self.last_visited.get(&country.id))
+            .copied()
+    }
+
+    /// When the country was visited, `None` if it is not visited or the visit has no date.
+    pub fn visit_date(&self, country: &Country) -> Option<NaiveDate> {
+        self.visit_dates.get(&country.id).copied()
+    }
+
+    /// The day the visit took place, or for visits without a date the day it was logged.
+    pub fn visited_on(&self, country: &Country) -> Option<NaiveDate> {
+        self.visit_date(country).or(self.last_visited(country).map(|time| time.date()))
+    }
+
+    pub fn set_visit_date(&mut self, country: &Country, date: Option<NaiveDate>) {
+        match date {
+            Some(date) if self.is_visited(country) => self.visit_dates.insert(country.id, date),
+            _ => self.visit_dates.remove(&country.id),
+        };
+    }
+
+    pub fn visit_dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.visit_dates.values().copied()
+    }
+
+    /// Earliest and latest visit date.
+    pub fn visit_date_range(&self) -> Option<(NaiveDate, NaiveDate)> {
+        Some((self.visit_dates().min()?, self.visit_dates().max()?))
+    }
+
+    /// Kind of the visit as it was on the given day, `None` as of any day means now. Visits
+    /// without a date count from the start.
+    pub fn visit_kind_as_of(&self, country: &Country, as_of: Option<NaiveDate>) -> Option<VisitKind> {
+        match (self.visit_date(country), as_of) {
+            (Some(date), Some(as_of)) if date > as_of => None,
+            _ => self.visit_kind(country),
+        }
+    }
+
+    /// How often the country was marked as visited.
+    pub fn visit_count(&self, country: &Country) -> usize {
+        self.visit_counts.get(&country.id).copied().unwrap_or_default()
+    }
+
+    /// In how many profiles the country is visited.
+    pub fn visit_rows(&self, country: &Country) -> usize {
+        self.visit_rows.get(&country.id).copied().unwrap_or_default()
+    }
+
+    /// The personal description as last written, which may be newer than the one of a copied
+    /// `Country`.
+    pub fn description(&self, country: &Country) -> Option<&str> {
+        self.countries.iter()
+            .find(|stored| stored.id == country.id)
+            .and_then(|stored| stored.description.as_deref())
+    }
+
+    pub fn set_description(&mut self, country: &Country, description: Option<String>) {
+        if let Some(stored) = self.countries.iter_mut().find(|stored| stored.id == country.id) {
+            stored.description = description;
+        }
+    }
+
+    /// Number of visited countries, countries only passed through in transit are counted only
+    /// if `include_transit`.
+    pub fn visited_count(&self, include_transit: bool) -> usize {
+        self.visited.values()
+            .filter(|kind| include_transit || **kind != VisitKind::Transit)
+            .count()
+    }
+
+    pub fn country_count(&self) -> usize {
+        self.countries.len()
+    }
+
+    pub fn wish(&self, country: &Country) -> Option<Wish> {
+        self.wishes.get(&country.id).copied()
+    }
+
+    pub fn set_wish(&mut self, country: &Country, wish: Option<Wish>) {
+        match wish {
+            Some(wish) => self.wishes.insert(country.id, wish),
+            None => self.wishes.remove(&country.id),
+        };
+    }
+
+    /// Goals of the active profile with their ids, in the order they were set.
+    pub fn goals(&self) -> &[(i32, Goal)] {
+        &self.goals
+    }
+
+    pub fn add_goal(&mut self, id: i32, goal: Goal) {
+        self.goals.push((id, goal));
+    }
+
+    pub fn remove_goal(&mut self, id: i32) {
+        self.goals.retain(|(goal_id, _)| *goal_id != id);
+    }
+
+    /// Progress towards the goal as reached and needed count, see `Goal::progress`.
+    pub fn goal_progress(&self, goal: &Goal) -> (usize, usize) {
+        goal.progress(self.countries_with_visit_status().map(|(country, visited)| (country, visited, self.visited_on(country))))
+    }
+
+    /// The travel log the active profile is compared with, if any.
+    pub fn compared_with(&self) -> Option<&TravelLog> {
+        self.compared_with.as_ref()
+    }
+
+    pub fn compare_with(&mut self, travel_log: Option<TravelLog>) {
+        self.compared_with = travel_log;
+    }
+
+    /// Where the country ends up when comparing the active profile with another travel log.
+    /// `None` if nobody visited it or if nothing is being compared.
+    pub fn comparison_side(&self, country: &Country) -> Option<ComparisonSide> {
+        let compared_with = self.compared_with.as_ref()?;
+        ComparisonSide::of(self.is_visited(country), compared_with.has_visited(country))
+    }
+
+    pub fn apply(&mut self, change: &VisitChange) {
+        match change {
+            VisitChange::Visit(country) => {
+                if !self.is_visited(country) {
+                    *self.visit_counts.entry(country.id).or_default() += 1;
+                }
+                self.set_visited(country.id, true);
+            }
+            VisitChange::Unvisit(country) => self.set_visited(country.id, false),
+        }
+    }
+
+    /// Takes over the state loaded again after an undo or redo, keeping the comparison.
+    pub fn reload(&mut self, loaded: AppState) {
+        let compared_with = self.compared_with.take();
+        *self = Self { compared_with, ..loaded };
+    }
+
+    fn set_visited(&mut self, country_id: i32, visited: bool) {
+        if visited {
+            if !self.visited.contains_key(&country_id) {
+                *self.visit_rows.entry(country_id).or_default() += 1;
+            }
+            self.visited.entry(country_id).or_default();
+            self.last_visited.insert(country_id, Utc::now().naive_utc());
+            self.visit_dates.entry(country_id).or_insert(Local::now().date_naive());
+        } else if self.visited.remove(&country_id).is_some() {
+            self.visit_dates.remove(&country_id);
+            if let Some(rows) = self.visit_rows.get_mut(&country_id) {
+                *rows = rows.saturating_sub(1);
+            }
+        }
+    }
+}
+
+/// A change to the visit status of a country. It is written to the database off the UI thread
+/// and only applied to the state once the write succeeded.
+#[derive(Debug, Clone)]
+pub enum VisitChange {
+    Visit(Country),
+    Unvisit(Country),
+}
+
+impl VisitChange {
+    pub fn country(&self) -> &Country {
+        match self {
+            VisitChange::Visit(country) => country,
+            VisitChange::Unvisit(country) => country,
+        }
+    }
+
+    /// Writes the changes as a single change set, so that they are undone together.
+    pub async fn write_all(changes: Vec<VisitChange>, profile_id: i32) -> Result<(), DatabaseError> {
+        write_change_set(profile_id, |connection, change_set| {
+            for change in &changes {
+                match change {
+                    VisitChange::Visit(country) => database::visit_country(connection, change_set, country)?,
+                    VisitChange::Unvisit(country) => database::unvisit_country(connection, change_set, country)?,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Writes a change made in the user interface as a change set of its own.
+fn write_change_set<T>(profile_id: i32, change: impl FnOnce(&mut SqliteConnection, ChangeSet) -> Result<T, diesel::result::Error>) -> Result<T, DatabaseError> {
+    let mut connection = database::connection()?;
+    Ok(database::in_change_set(&mut connection, profile_id, ChangeSource::Gui, change)?)
+}
+
+pub async fn write_visit_kind(profile_id: i32, country_id: i32, visit_kind: VisitKind) -> Result<(), DatabaseError> {
+    write_change_set(profile_id, |connection, change_set| database::set_visit_kind(connection, change_set, country_id, visit_kind))
+}
+
+/// Writes the description of the country, which all profiles share. The change is logged for
+/// the profile that made it.
+pub async fn write_description(profile_id: i32, country_id: i32, description: Option<String>) -> Result<(), DatabaseError> {
+    write_change_set(profile_id, |connection, change_set| database::set_description(connection, change_set, country_id, description.as_deref()))
+}
+
+pub async fn write_visit_date(profile_id: i32, country_id: i32, date: Option<NaiveDate>) -> Result<(), DatabaseError> {
+    write_change_set(profile_id, |connection, change_set| database::set_visit_date(connection, change_set, country_id, date))
+}
+
+pub async fn write_wish(profile_id: i32, country_id: i32, wish: Option<Wish>) -> Result<(), DatabaseError> {
+    write_change_set(profile_id, |connection, change_set| database::set_wish(connection, change_set, country_id, wish))
+}
+
+pub async fn write_goal(profile_id: i32, goal: Goal) -> Result<i32, DatabaseError> {
+    write_change_set(profile_id, |connection, change_set| database::insert_goal(connection, change_set, goal))
+}
+
+pub async fn delete_goal(profile_id: i32, goal_id: i32) -> Result<(), DatabaseError> {
+    write_change_set(profile_id, |connection, change_set| database::delete_goal(connection, change_set, goal_id))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HistoryStep {
+    Undo,
+    Redo,
+}
+
+impl HistoryStep {
+    /// Undoes or redoes the last change set of the profile and loads its state again, as the
+    /// change set may have touched anything from visits to goals.
+    pub async fn perform(self, profile: Profile) -> Result<AppState, DatabaseError> {
+        let mut connection = database::connection()?;
+        match self {
+            HistoryStep::Undo => database::undo(&mut connection, profile.id, ChangeSource::Gui)?,
+            HistoryStep::Redo => database::redo(&mut connection, profile.id, ChangeSource::Gui)?,
+        };
+        AppState::load(&mut connection, profile)
+    }
+}
+
+const RECENT_CHANGES: i64 = 50;
+
+pub async fn recent_changes(profile_id: i32) -> Result<Vec<(ChangeLogEntry, Option<Country>)>, DatabaseError> {
+    let mut connection = database::connection()?;
+    Ok(database::recent_changes(&mut connection, profile_id, RECENT_CHANGES)?)
+}
+
+pub async fn switch_profile(profile: Profile) -> Result<AppState, DatabaseError> {
+    let mut connection = database::connection()?;
+    AppState::load(&mut connection, profile)
+}
+
+/// Loads the state of the profile with the given name, creating the profile if it is new.
+pub async fn create_profile(profile_name: String) -> Result<AppState, DatabaseError> {
+    let mut connection = database::connection()?;
+    let profile = database::profile_by_name_or_create(&mut connection, &profile_name)?;
+    AppState::load(&mut connection, profile)
+}
+
+/// Loads the visited countries of another profile to compare the active one with.
+pub async fn load_travel_log(profile: Profile) -> Result<TravelLog, DatabaseError> {
+    let mut connection = database::connection()?;
+    let visited = database::visited_countries(&mut connection, profile.id)?;
+    Ok(TravelLog::new(profile.name, visited))
+}
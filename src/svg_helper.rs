@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::iter::Peekable;
 use std::ops::{Add, Mul};
 use std::slice::Iter;
@@ -16,9 +17,12 @@ lazy_static! {
             let mut map = HashMap::new();
             for file in SVG_FILES.files() {
                 let name = file.path().file_name().unwrap().to_str().unwrap()[0..2].to_string();
-                let contents = file.contents_utf8().unwrap().to_string();
-                let polygons = polygons_from_svg(&contents);
-                map.insert(name, polygons.into_iter().map(scale_polygon).collect());
+                match file.contents_utf8().ok_or(SvgError::Encoding).and_then(polygons_from_svg) {
+                    Ok(polygons) => {
+                        map.insert(name, polygons.into_iter().map(scale_polygon).collect());
+                    }
+                    Err(e) => eprintln!("Skipping borders of {}: {}", name, e),
+                }
             }
             map
     };
@@ -45,22 +49,69 @@ impl Mul<&Point> for Point {
 
 pub struct Polygon(pub Vec<Point>);
 
+#[derive(Debug)]
+pub enum SvgError {
+    Encoding,
+    Read(std::io::Error),
+    PathData(svg::parser::Error),
+    MissingCoordinate,
+    RelativeWithoutStart,
+}
+
+impl Display for SvgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgError::Encoding => write!(f, "file is not valid UTF-8"),
+            SvgError::Read(e) => write!(f, "cannot read svg: {}", e),
+            SvgError::PathData(e) => write!(f, "invalid path data: {}", e),
+            SvgError::MissingCoordinate => write!(f, "path command is missing a coordinate"),
+            SvgError::RelativeWithoutStart => write!(f, "relative path command without a starting point"),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SvgError::Read(e) => Some(e),
+            SvgError::PathData(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl Polygon {
     pub fn iter(&self) -> Iter<'_, Point> {
         self.0.iter()
     }
+
+    /// Whether the point lies inside, by counting the edges a ray to the right of it crosses.
+    pub fn contains(&self, point: &Point) -> bool {
+        let Point(x, y) = *point;
+        let edges = self.0.iter().zip(self.0.iter().cycle().skip(1));
+        edges
+            .filter(|(a, b)| (a.1 > y) != (b.1 > y))
+            .filter(|(a, b)| x < a.0 + (y - a.1) / (b.1 - a.1) * (b.0 - a.0))
+            .count() % 2 == 1
+    }
+
+    /// Whether the polygon and the axis-aligned box between `min` and `max` overlap, either by a
+    /// border point inside the box or by the box lying inside the polygon.
+    pub fn overlaps_box(&self, min: &Point, max: &Point) -> bool {
+        let in_box = |point: &&Point| (min.0..=max.0).contains(&point.0) && (min.1..=max.1).contains(&point.1);
+        self.0.iter().any(|point| in_box(&point)) || self.contains(&Point((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0))
+    }
 }
 
-fn polygons_from_svg(source: &str) -> Vec<Polygon> {
-    let svg = svg::read(source).unwrap();
+fn polygons_from_svg(source: &str) -> Result<Vec<Polygon>, SvgError> {
+    let svg = svg::read(source).map_err(SvgError::Read)?;
     let mut paths: Vec<Polygon> = vec![];
     for event in svg {
         if let Event::Tag(svg::node::element::tag::Path, _, attributes) = event {
-            let data = attributes.get("d");
-            if data.is_none() {
+            let Some(data) = attributes.get("d") else {
                 continue;
-            }
-            let data = Data::parse(data.unwrap()).unwrap();
+            };
+            let data = Data::parse(data).map_err(SvgError::PathData)?;
             let mut current_path: Vec<Point> = vec![];
             let mut last_point: Option<Point> = None;
             for command in data.iter() {
@@ -69,14 +120,16 @@ fn polygons_from_svg(source: &str) -> Vec<Polygon> {
                         let mut iterator = params.iter().peekable();
                         match pos {
                             Position::Absolute => {
-                                last_point = Some(point_from_iter(&mut iterator));
-                                current_path.push(last_point.clone().unwrap());
+                                let point = point_from_iter(&mut iterator)?;
+                                current_path.push(point.clone());
+                                last_point = Some(point);
                             }
                             Position::Relative => {
                                 paths.push(Polygon(current_path.clone()));
                                 current_path.clear();
-                                last_point = Some(rel_point_from_iter(&mut iterator, &last_point.unwrap()));
-                                current_path.push(last_point.clone().unwrap());
+                                let point = rel_point_from_iter(&mut iterator, last_point.as_ref())?;
+                                current_path.push(point.clone());
+                                last_point = Some(point);
                             }
                         }
                     }
@@ -85,8 +138,9 @@ fn polygons_from_svg(source: &str) -> Vec<Polygon> {
                         match pos {
                             Position::Absolute => {
                                 loop {
-                                    last_point = Some(point_from_iter(&mut iterator));
-                                    current_path.push(last_point.clone().unwrap());
+                                    let point = point_from_iter(&mut iterator)?;
+                                    current_path.push(point.clone());
+                                    last_point = Some(point);
                                     if iterator.peek().is_none() {
                                         break;
                                     }
@@ -94,8 +148,9 @@ fn polygons_from_svg(source: &str) -> Vec<Polygon> {
                             }
                             Position::Relative => {
                                 loop {
-                                    last_point = Some(rel_point_from_iter(&mut iterator, &last_point.unwrap()));
-                                    current_path.push(last_point.clone().unwrap());
+                                    let point = rel_point_from_iter(&mut iterator, last_point.as_ref())?;
+                                    current_path.push(point.clone());
+                                    last_point = Some(point);
                                     if iterator.peek().is_none() {
                                         break;
                                     }
@@ -112,7 +167,7 @@ fn polygons_from_svg(source: &str) -> Vec<Polygon> {
             }
         }
     }
-    paths
+    Ok(paths)
 }
 
 fn scale_polygon(polygon: Polygon) -> Polygon {
@@ -121,11 +176,36 @@ fn scale_polygon(polygon: Polygon) -> Polygon {
         .collect())
 }
 
-fn point_from_iter(iter: &mut Peekable<Iter<Number>>) -> Point {
-    Point(*iter.next().unwrap(), *iter.next().unwrap())
+fn point_from_iter(iter: &mut Peekable<Iter<Number>>) -> Result<Point, SvgError> {
+    let x = iter.next().ok_or(SvgError::MissingCoordinate)?;
+    let y = iter.next().ok_or(SvgError::MissingCoordinate)?;
+    Ok(Point(*x, *y))
+}
+
+fn rel_point_from_iter(iter: &mut Peekable<Iter<Number>>, reference: Option<&Point>) -> Result<Point, SvgError> {
+    let reference = reference.ok_or(SvgError::RelativeWithoutStart)?;
+    let abs = point_from_iter(iter)?;
+    Ok(Point(abs.0 + reference.0, abs.1 + reference.1))
 }
+#[cfg(test)]
+mod tests {
+    use crate::svg_helper::{Point, Polygon};
 
-fn rel_point_from_iter(iter: &mut Peekable<Iter<Number>>, reference: &Point) -> Point {
-    let abs = point_from_iter(iter);
-    Point(abs.0 + reference.0, abs.1 + reference.1)
-}
\ No newline at end of file
+    fn square() -> Polygon {
+        Polygon(vec![Point(0.2, 0.2), Point(0.4, 0.2), Point(0.4, 0.4), Point(0.2, 0.4)])
+    }
+
+    #[test]
+    fn points_inside_are_contained() {
+        assert!(square().contains(&Point(0.3, 0.3)));
+        assert!(!square().contains(&Point(0.5, 0.3)));
+        assert!(!square().contains(&Point(0.3, 0.1)));
+    }
+
+    #[test]
+    fn boxes_overlap_by_corner_or_from_inside() {
+        assert!(square().overlaps_box(&Point(0.35, 0.35), &Point(0.6, 0.6)));
+        assert!(square().overlaps_box(&Point(0.29, 0.29), &Point(0.31, 0.31)));
+        assert!(!square().overlaps_box(&Point(0.5, 0.5), &Point(0.6, 0.6)));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use chrono::NaiveDate;
+use diesel::SqliteConnection;
+use crate::database;
+use crate::database::DatabaseError;
+use crate::geocode;
+use crate::gpx::read_gpx;
+use crate::importer::ImportError;
+use crate::models::{ChangeSource, Country};
+use crate::photos::read_photo_points;
+
+/// A recorded position in WGS84 degrees, with the day it was recorded on if known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpsPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub date: Option<NaiveDate>,
+}
+
+/// A country recorded positions lie in, to be marked as visited once the user accepts it.
+#[derive(Debug, Clone)]
+pub struct VisitProposal {
+    pub country: Country,
+    /// First and last day with a position in the country.
+    pub first: Option<NaiveDate>,
+    pub last: Option<NaiveDate>,
+    pub points: usize,
+}
+
+impl VisitProposal {
+    /// The visit date after accepting the proposal, given the date of the visit so far. The
+    /// latest known visit wins.
+    pub fn visit_date(&self, current: Option<NaiveDate>) -> Option<NaiveDate> {
+        current.max(self.last)
+    }
+}
+
+/// Finds the countries the points lie in, ordered by the first day in each.
+pub fn propose(connection: &mut SqliteConnection, points: &[GpsPoint]) -> Result<Vec<VisitProposal>, diesel::result::Error> {
+    let mut by_country: HashMap<&str, (Option<NaiveDate>, Option<NaiveDate>, usize)> = HashMap::new();
+    for point in points {
+        let Some(iso2) = geocode::iso2_at(point.latitude, point.longitude) else {
+            continue;
+        };
+        let (first, last, count) = by_country.entry(iso2).or_insert((None, None, 0));
+        if let Some(date) = point.date {
+            *first = Some(first.map_or(date, |first| first.min(date)));
+            *last = Some(last.map_or(date, |last| last.max(date)));
+        }
+        *count += 1;
+    }
+    let mut proposals = vec![];
+    for (iso2, (first, last, points)) in by_country {
+        if let Some(country) = database::country_by_iso2(connection, iso2)? {
+            proposals.push(VisitProposal { country, first, last, points });
+        }
+    }
+    proposals.sort_by(|a, b| (a.first.is_none(), a.first, &a.country.name).cmp(&(b.first.is_none(), b.first, &b.country.name)));
+    Ok(proposals)
+}
+
+/// Marks the proposed countries as visited in one change set, so the import can be undone as a
+/// whole, and moves their visit dates to the last day in each if that is later.
+pub fn accept(connection: &mut SqliteConnection, profile_id: i32, proposals: &[VisitProposal]) -> Result<(), diesel::result::Error> {
+    database::in_change_set(connection, profile_id, ChangeSource::Import, |connection, change_set| {
+        let visit_dates = database::visit_dates(connection, profile_id)?;
+        for proposal in proposals {
+            let country = &proposal.country;
+            database::visit_country(connection, change_set, country)?;
+            let date = proposal.visit_date(visit_dates.get(&country.id).copied());
+            database::set_visit_date(connection, change_set, country.id, date)?;
+        }
+        Ok(())
+    })
+}
+
+pub fn read_gpx_files(paths: &[PathBuf]) -> Result<Vec<GpsPoint>, ImportError> {
+    let mut points = vec![];
+    for path in paths {
+        points.extend(read_gpx(&fs::read_to_string(path)?)?);
+    }
+    Ok(points)
+}
+
+pub async fn propose_from_gpx(paths: Vec<PathBuf>) -> Result<Vec<VisitProposal>, ImportError> {
+    let points = read_gpx_files(&paths)?;
+    let mut connection = database::connection()?;
+    Ok(propose(&mut connection, &points)?)
+}
+
+pub async fn propose_from_photos(directory: PathBuf) -> Result<Vec<VisitProposal>, ImportError> {
+    let points = read_photo_points(&directory)?;
+    let mut connection = database::connection()?;
+    Ok(propose(&mut connection, &points)?)
+}
+
+pub async fn write_accepted(profile_id: i32, proposals: Vec<VisitProposal>) -> Result<(), DatabaseError> {
+    let mut connection = database::connection()?;
+    accept(&mut connection, profile_id, &proposals)?;
+    Ok(())
+}
@@ -1,5 +1,18 @@
+pub mod achievements;
+pub mod bulk_actions;
 pub mod country_filter;
+pub mod error_banner;
+pub mod goals_panel;
+pub mod gps_import;
 pub mod country_list;
+pub mod comparison_list;
+pub mod profile_switcher;
+pub mod proposal_review;
+pub mod recent_activity;
+pub mod report_export;
+pub mod settings_panel;
+pub mod statistics;
+pub mod timeline;
 pub mod world_map;
 
 pub mod country_info;
\ No newline at end of file
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use iced::widget::{column, row};
+use crate::achievements::{ACHIEVEMENTS, unlocked};
+use crate::state::AppState;
+
+/// Badges for the achievements unlocked so far, shown in place of the country info while it is
+/// open, and a banner announcing newly unlocked ones.
+pub struct AchievementsPanel {
+    open: bool,
+    /// Names of the achievements unlocked since the banner was last dismissed.
+    announced: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AchievementsMessage {
+    Toggle,
+    Dismiss,
+}
+
+impl AchievementsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            announced: Vec::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn announce(&mut self, names: impl IntoIterator<Item = String>) {
+        self.announced.extend(names);
+    }
+
+    pub fn view_toggle(&self) -> iced::Element<'_, AchievementsMessage> {
+        let label = if self.open { "Close achievements" } else { "Achievements" };
+        iced::widget::button(label)
+            .on_press(AchievementsMessage::Toggle)
+            .into()
+    }
+
+    pub fn view_announcement(&self) -> iced::Element<'_, AchievementsMessage> {
+        if self.announced.is_empty() {
+            return column!().into();
+        }
+        let banner = row!(
+            iced::widget::text(format!("Achievement unlocked: {}", self.announced.join(", "))).width(iced::Length::Fill),
+            iced::widget::button("Show").on_press(AchievementsMessage::Toggle),
+            iced::widget::button("Dismiss").on_press(AchievementsMessage::Dismiss),
+        )
+        .spacing(5)
+        .padding(5)
+        .align_items(iced::Alignment::Center);
+        iced::widget::container(banner)
+            .style(iced::theme::Container::Box)
+            .width(iced::Length::Fill)
+            .into()
+    }
+
+    pub fn view(&self, state: &AppState) -> iced::Element<'_, AchievementsMessage> {
+        let unlocked = unlocked(state);
+        let visited: HashSet<&str> = state.countries()
+            .filter(|country| state.is_visited(country))
+            .map(|country| country.iso2.as_str())
+            .collect();
+        let badges = unlocked.iter()
+            .map(|(achievement, date)| {
+                let date = date.map(|date| format!("unlocked on {}", date)).unwrap_or("unlocked".to_string());
+                column!(
+                    iced::widget::text(&achievement.name).size(18),
+                    iced::widget::text(&achievement.description).size(14),
+                    iced::widget::text(date).size(12),
+                ).into()
+            })
+            .collect();
+        let locked = ACHIEVEMENTS.iter()
+            .filter(|achievement| !unlocked.iter().any(|(unlocked, _)| unlocked.id == achievement.id))
+            .map(|achievement| {
+                let (reached, needed) = achievement.progress(&visited);
+                column!(
+                    iced::widget::text(&achievement.name),
+                    iced::widget::text(&achievement.description).size(14),
+                    iced::widget::progress_bar(0.0..=needed.max(1) as f32, reached as f32).height(iced::Length::Fixed(8.0)),
+                    iced::widget::text(format!("{} of {} {}", reached, needed, achievement.counted())).size(12),
+                ).into()
+            })
+            .collect();
+        let panel = column!(
+            iced::widget::text("Achievements").size(25),
+            iced::widget::column(badges).spacing(10),
+            iced::widget::horizontal_rule(10),
+            iced::widget::text("Still to unlock"),
+            iced::widget::column(locked).spacing(10),
+        )
+        .spacing(5)
+        .padding(5);
+        iced::widget::scrollable(panel)
+            .width(iced::Length::Fixed(250.0))
+            .into()
+    }
+
+    pub fn update(&mut self, msg: AchievementsMessage) {
+        match msg {
+            AchievementsMessage::Toggle => {
+                self.open = !self.open;
+                self.announced.clear();
+            }
+            AchievementsMessage::Dismiss => self.announced.clear(),
+        }
+    }
+}
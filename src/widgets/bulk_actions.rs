@@ -0,0 +1,77 @@
+use iced::widget::{column, row};
+use crate::models::Country;
+
+/// Actions on all selected countries, shown in place of the country info while more than one
+/// country is selected.
+pub struct BulkActions {
+    export_path: String,
+    /// Where the selection was last exported to.
+    exported_to: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum BulkActionsMessage {
+    MarkVisited,
+    MarkUnvisited,
+    ExportPath(String),
+    Export,
+    ClearSelection,
+}
+
+impl BulkActions {
+    pub fn new() -> Self {
+        Self {
+            export_path: "selection.txt".to_string(),
+            exported_to: None,
+        }
+    }
+
+    pub fn export_path(&self) -> &str {
+        &self.export_path
+    }
+
+    pub fn exported(&mut self, path: String) {
+        self.exported_to = Some(path);
+    }
+
+    pub fn view<'a>(&'a self, countries: &[&'a Country]) -> iced::Element<'a, BulkActionsMessage> {
+        let names = countries.iter()
+            .map(|country| iced::widget::text(&country.name).size(14).into())
+            .collect();
+        let export_path = iced::widget::text_input("file", &self.export_path)
+            .on_input(BulkActionsMessage::ExportPath)
+            .on_submit(BulkActionsMessage::Export);
+        let exported_to = self.exported_to.as_ref()
+            .map(|path| format!("exported to {}", path))
+            .unwrap_or_default();
+        column!(
+            iced::widget::text(format!("{} countries selected", countries.len())).size(25),
+            row!(
+                iced::widget::button("Visited").on_press(BulkActionsMessage::MarkVisited),
+                iced::widget::button("Not visited").on_press(BulkActionsMessage::MarkUnvisited),
+            ).spacing(5),
+            iced::widget::text("export as a simple import file"),
+            row!(
+                export_path,
+                iced::widget::button("Export").on_press(BulkActionsMessage::Export),
+            ).spacing(5),
+            iced::widget::text(exported_to).size(12),
+            iced::widget::button("Clear selection").on_press(BulkActionsMessage::ClearSelection),
+            iced::widget::scrollable(iced::widget::column(names)).height(iced::Length::Fill),
+        )
+        .spacing(5)
+        .padding(5)
+        .width(iced::Length::Fixed(250.0))
+        .into()
+    }
+
+    pub fn update(&mut self, msg: BulkActionsMessage) {
+        match msg {
+            BulkActionsMessage::ExportPath(path) => self.export_path = path,
+            BulkActionsMessage::MarkVisited
+            | BulkActionsMessage::MarkUnvisited
+            | BulkActionsMessage::Export
+            | BulkActionsMessage::ClearSelection => {}
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use iced::widget::column;
+use crate::compare::compare;
+use crate::state::AppState;
+use crate::widgets::world_map::comparison_color;
+
+/// Read-only list of the countries visited by both compared travel logs or only by one of them.
+/// The headings are coloured like the countries on the map, so they double as its legend.
+pub struct ComparisonList;
+
+impl ComparisonList {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn view<'a, Message: 'static>(&self, state: &'a AppState) -> iced::Element<'a, Message> {
+        let Some(compared_with) = state.compared_with() else {
+            return column!().into();
+        };
+        let comparison = compare(
+            state.countries(),
+            |country| state.is_visited(country),
+            |country| compared_with.has_visited(country),
+        );
+        let sides = comparison.into_iter()
+            .map(|(side, countries)| {
+                let title = format!("{} ({})", side.title(&state.profile().name, &compared_with.name), countries.len());
+                let countries = countries.into_iter()
+                    .map(|country| iced::widget::text(&country.name).into())
+                    .collect::<Vec<iced::Element<'a, Message>>>();
+                column!(
+                    iced::widget::text(title).size(16).style(comparison_color(side)),
+                    iced::widget::scrollable(iced::widget::column(countries))
+                        .height(iced::Length::Fill),
+                )
+                .width(iced::Length::FillPortion(1))
+                .into()
+            })
+            .collect::<Vec<iced::Element<'a, Message>>>();
+        column!(
+            iced::widget::text("Comparison").size(18),
+            iced::widget::row(sides).spacing(10),
+        )
+        .padding(5)
+        .height(iced::Length::Fixed(160.0))
+        .into()
+    }
+}
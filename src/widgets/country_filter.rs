@@ -1,32 +1,57 @@
-use iced::widget::{column, checkbox};
+use iced::Command;
+use iced::widget::{column, checkbox, row, text_input};
+use crate::list_options::{CountrySort, VisitFilter};
+
+const SEARCH_ID: &str = "country-search";
 
 #[derive(Debug, Clone)]
 pub enum CountryFiltersMessage {
     SearchString(String),
-    OnlyVisited(bool),
+    Visited(VisitFilter),
+    OnlyWishlist(bool),
+    Sort(CountrySort),
+    GroupByContinent(bool),
+    /// Enter was pressed in the search field.
+    Submit,
 }
 
 pub struct CountryFilters {
     search_string: String,
-    only_visited: bool,
+    visited: VisitFilter,
+    only_wishlist: bool,
+    sort: CountrySort,
+    group_by_continent: bool,
 }
 
 impl CountryFilters {
     pub fn new() -> Self {
         Self {
             search_string: String::new(),
-            only_visited: false,
+            visited: VisitFilter::All,
+            only_wishlist: false,
+            sort: CountrySort::Name,
+            group_by_continent: false,
         }
     }
 
     pub fn view(&self) -> iced::Element<'_, CountryFiltersMessage> {
         let search = iced::widget::text_input("Search", &self.search_string)
-            .on_input(CountryFiltersMessage::SearchString);
-        let only_visited = checkbox("only visited", self.only_visited, CountryFiltersMessage::OnlyVisited);
+            .id(text_input::Id::new(SEARCH_ID))
+            .on_input(CountryFiltersMessage::SearchString)
+            .on_submit(CountryFiltersMessage::Submit);
+        let visited = VisitFilter::ALL.into_iter()
+            .map(|filter| iced::widget::radio(filter.to_string(), filter, Some(self.visited), CountryFiltersMessage::Visited).into())
+            .collect();
+        let only_wishlist = checkbox("only wishlist", self.only_wishlist, CountryFiltersMessage::OnlyWishlist);
+        let sort = iced::widget::pick_list(&CountrySort::ALL[..], Some(self.sort), CountryFiltersMessage::Sort);
+        let group_by_continent = checkbox("by continent", self.group_by_continent, CountryFiltersMessage::GroupByContinent);
         column!(
             search,
-            only_visited,
+            iced::widget::row(visited).spacing(10),
+            only_wishlist,
+            row!(sort, group_by_continent).spacing(10).align_items(iced::Alignment::Center),
         )
+        .spacing(5)
         .width(iced::Length::Fixed(250.0))
         .into()
     }
@@ -34,7 +59,20 @@ impl CountryFilters {
     pub fn update(&mut self, msg: CountryFiltersMessage) {
         match msg {
             CountryFiltersMessage::SearchString(string) => self.search_string = string,
-            CountryFiltersMessage::OnlyVisited(only_visited) => self.only_visited = only_visited,
+            CountryFiltersMessage::Visited(visited) => self.visited = visited,
+            CountryFiltersMessage::Sort(sort) => self.sort = sort,
+            CountryFiltersMessage::GroupByContinent(group_by_continent) => self.group_by_continent = group_by_continent,
+            CountryFiltersMessage::OnlyWishlist(only_wishlist) => self.only_wishlist = only_wishlist,
+            CountryFiltersMessage::Submit => {}
         }
     }
+
+    pub fn focus_search<Message: 'static>() -> Command<Message> {
+        text_input::focus(text_input::Id::new(SEARCH_ID))
+    }
+
+    /// Takes the keyboard focus away from the search field, so typing no longer ends up in it.
+    pub fn unfocus_search<Message: 'static>() -> Command<Message> {
+        text_input::focus(text_input::Id::unique())
+    }
 }
\ No newline at end of file
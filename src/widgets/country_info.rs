@@ -1,45 +1,238 @@
-use iced::widget::{checkbox, column};
-use crate::models::Country;
+use chrono::NaiveDate;
+use iced::widget::{checkbox, column, row};
+use crate::adjacency;
+use crate::base_data::BaseDataCountry;
+use crate::flag_helper::FLAGS;
+use crate::models::{Country, VisitKind, Wish, WishlistStatus};
+use crate::state::AppState;
 
 pub struct CountryInfo {
     country: Country,
-    visited: bool,
+    /// Target year while it is being edited, `None` shows the stored one.
+    target_year: Option<String>,
+    /// Visit date while it is being edited, `None` shows the stored one.
+    visit_date: Option<String>,
+    /// Description while it is being edited, `None` shows the stored one.
+    description: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CountryInfoMessage {
     VisitCountry(Country),
     UnvisitCountry(Country),
+    SetVisitKind(Country, VisitKind),
+    VisitDate(String),
+    SetVisitDate(Country, Option<NaiveDate>),
+    SetWish(Country, Option<Wish>),
+    TargetYear(String),
+    Description(String),
+    SetDescription(Country, Option<String>),
+    Select(Country),
 }
 
 impl CountryInfo {
-    pub fn new(country: Country, visited: bool) -> Self {
+    pub fn new(country: Country) -> Self {
         Self {
             country,
-            visited,
+            target_year: None,
+            visit_date: None,
+            description: None,
         }
     }
 
-    pub fn view(&self) -> iced::Element<'_, CountryInfoMessage> {
-        let visited = checkbox("visited", self.visited, |visited| {
+    pub fn country(&self) -> &Country {
+        &self.country
+    }
+
+    pub fn view<'a>(&'a self, state: &'a AppState) -> iced::Element<'a, CountryInfoMessage> {
+        let visited = checkbox("visited", state.is_visited(&self.country), |visited| {
             if visited {
                 CountryInfoMessage::VisitCountry(self.country.clone())
             } else {
                 CountryInfoMessage::UnvisitCountry(self.country.clone())
             }
         });
-        column!(
+        let info = column!(
+            self.view_flag(),
             iced::widget::text(&self.country.name)
             .size(25)
             .width(iced::Length::Fixed(200.0)),
+            self.view_facts(state),
             visited,
+            self.view_visit_kind(state.visit_kind(&self.country)),
+            self.view_visit_date(state),
+            self.view_wish(state.wish(&self.country)),
+            self.view_description(state.description(&self.country)),
+            self.view_unvisited_neighbours(state),
+        )
+        .spacing(5);
+        iced::widget::scrollable(info)
+            .width(iced::Length::Fixed(250.0))
+            .into()
+    }
+
+    fn view_flag(&self) -> iced::Element<'_, CountryInfoMessage> {
+        match FLAGS.get(&self.country.iso2) {
+            Some(flag) => iced::widget::image(flag.clone())
+                .width(iced::Length::Fixed(200.0))
+                .into(),
+            None => column!().into(),
+        }
+    }
+
+    fn view_facts(&self, state: &AppState) -> iced::Element<'_, CountryInfoMessage> {
+        let mut facts = vec![("ISO codes", format!("{} / {}", self.country.iso2, self.country.iso3))];
+        if let Some(base_data) = BaseDataCountry::by_iso2(&self.country.iso2) {
+            facts.push(("continent", base_data.continent.to_string()));
+            if let Some(capital) = &base_data.capital {
+                facts.push(("capital", capital.clone()));
+            }
+            facts.push(("population", base_data.population_text()));
+            facts.push(("area", base_data.area_text()));
+        }
+        facts.push(("visited in", format!("{} of {} profiles", state.visit_rows(&self.country), state.profiles().len())));
+        let rows = facts.into_iter()
+            .map(|(label, value)| {
+                row!(
+                    iced::widget::text(label).size(14).width(iced::Length::Fixed(80.0)),
+                    iced::widget::text(value).size(14),
+                ).into()
+            })
+            .collect();
+        iced::widget::column(rows).into()
+    }
+
+    fn view_visit_kind(&self, visit_kind: Option<VisitKind>) -> iced::Element<'_, CountryInfoMessage> {
+        let Some(visit_kind) = visit_kind else {
+            return column!().into();
+        };
+        iced::widget::pick_list(
+            &VisitKind::ALL[..],
+            Some(visit_kind),
+            |visit_kind| CountryInfoMessage::SetVisitKind(self.country.clone(), visit_kind),
+        )
+        .into()
+    }
+
+    fn view_visit_date(&self, state: &AppState) -> iced::Element<'_, CountryInfoMessage> {
+        if !state.is_visited(&self.country) {
+            return column!().into();
+        }
+        let date = self.visit_date.clone()
+            .or(state.visit_date(&self.country).map(|date| date.to_string()))
+            .unwrap_or_default();
+        let mut input = iced::widget::text_input("YYYY-MM-DD", &date)
+            .on_input(CountryInfoMessage::VisitDate)
+            .width(iced::Length::Fixed(120.0));
+        if date.trim().is_empty() {
+            input = input.on_submit(CountryInfoMessage::SetVisitDate(self.country.clone(), None));
+        } else if let Ok(date) = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+            input = input.on_submit(CountryInfoMessage::SetVisitDate(self.country.clone(), Some(date)));
+        }
+        row!(iced::widget::text("visited on"), input)
+            .spacing(5)
+            .align_items(iced::Alignment::Center)
+            .into()
+    }
+
+    fn view_wish(&self, wish: Option<Wish>) -> iced::Element<'_, CountryInfoMessage> {
+        let status = iced::widget::pick_list(
+            &WishlistStatus::ALL[..],
+            wish.map(|wish| wish.status),
+            move |status| {
+                let wish = wish.map(|wish| Wish { status, ..wish }).unwrap_or(Wish::new(status));
+                CountryInfoMessage::SetWish(self.country.clone(), Some(wish))
+            },
+        )
+        .placeholder("not planned");
+        let Some(wish) = wish else {
+            return column!(status).into();
+        };
+        let priority = iced::widget::pick_list(
+            &Wish::PRIORITIES[..],
+            Some(wish.priority),
+            move |priority| CountryInfoMessage::SetWish(self.country.clone(), Some(Wish { priority, ..wish })),
+        );
+        let target_year = self.target_year.clone()
+            .or(wish.target_year.map(|year| year.to_string()))
+            .unwrap_or_default();
+        let submitted_wish = Wish { target_year: target_year.parse().ok(), ..wish };
+        let target_year = iced::widget::text_input("target year", &target_year)
+            .on_input(CountryInfoMessage::TargetYear)
+            .on_submit(CountryInfoMessage::SetWish(self.country.clone(), Some(submitted_wish)))
+            .width(iced::Length::Fixed(100.0));
+        column!(
+            row!(
+                status,
+                iced::widget::button("Remove").on_press(CountryInfoMessage::SetWish(self.country.clone(), None)),
+            ).spacing(5),
+            row!(iced::widget::text("priority"), priority).spacing(5),
+            target_year,
+        )
+        .spacing(5)
+        .into()
+    }
+
+    fn view_description(&self, stored: Option<&str>) -> iced::Element<'_, CountryInfoMessage> {
+        let description = self.description.clone()
+            .or(stored.map(str::to_string))
+            .unwrap_or_default();
+        let submitted = Some(description.trim().to_string()).filter(|description| !description.is_empty());
+        let save = CountryInfoMessage::SetDescription(self.country.clone(), submitted);
+        let input = iced::widget::text_input("personal notes", &description)
+            .on_input(CountryInfoMessage::Description)
+            .on_submit(save.clone());
+        let mut save_button = iced::widget::button("Save");
+        if self.description.is_some() {
+            save_button = save_button.on_press(save);
+        }
+        column!(
+            iced::widget::text("Description").size(18),
+            row!(input, save_button).spacing(5),
+        )
+        .spacing(5)
+        .into()
+    }
+
+    fn view_unvisited_neighbours<'a>(&self, state: &'a AppState) -> iced::Element<'a, CountryInfoMessage> {
+        let neighbours: Vec<&str> = adjacency::neighbours(&self.country.iso2).collect();
+        if neighbours.is_empty() {
+            return column!().into();
+        }
+        let unvisited = state.countries()
+            .filter(|country| neighbours.contains(&country.iso2.as_str()) && !state.is_visited(country))
+            .map(|country| {
+                iced::widget::button(iced::widget::text(&country.name))
+                    .on_press(CountryInfoMessage::Select(country.clone()))
+                    .style(iced::theme::Button::Text)
+                    .padding(2)
+                    .into()
+            })
+            .collect::<Vec<iced::Element<'a, CountryInfoMessage>>>();
+        let title = if unvisited.is_empty() {
+            "All neighbours visited"
+        } else {
+            "Unvisited neighbours"
+        };
+        column!(
+            iced::widget::text(title).size(18),
+            iced::widget::column(unvisited),
         ).into()
     }
 
     pub fn update(&mut self, msg: CountryInfoMessage) {
         match msg {
-            CountryInfoMessage::VisitCountry(_) => self.visited = true,
-            CountryInfoMessage::UnvisitCountry(_) => self.visited = false,
+            CountryInfoMessage::TargetYear(year) if year.chars().all(|c| c.is_ascii_digit()) => {
+                self.target_year = Some(year);
+            }
+            CountryInfoMessage::SetWish(_, _) => self.target_year = None,
+            CountryInfoMessage::VisitDate(date) if date.chars().all(|c| c.is_ascii_digit() || c == '-') => {
+                self.visit_date = Some(date);
+            }
+            CountryInfoMessage::SetVisitDate(_, _) => self.visit_date = None,
+            CountryInfoMessage::Description(description) => self.description = Some(description),
+            CountryInfoMessage::SetDescription(_, _) => self.description = None,
+            _ => {}
         }
     }
 }
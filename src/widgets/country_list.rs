@@ -1,63 +1,139 @@
-use iced::widget::{row, column};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use iced::Command;
+use iced::widget::{row, column, scrollable};
 use iced::widget::image::Handle;
 use itertools::Itertools;
-use crate::database;
+use crate::base_data::Continent;
 use crate::flag_helper::FLAGS;
+use crate::list_options::{CountrySort, VisitFilter};
 use crate::models::Country;
+use crate::state::AppState;
+
+const LIST_ID: &str = "country-list";
 
 pub struct CountryList {
     filter: String,
-    filter_only_visited: bool,
+    filter_visited: VisitFilter,
+    filter_only_wishlist: bool,
+    sort: CountrySort,
+    group_by_continent: bool,
+    /// Continent groups whose countries are hidden.
+    collapsed: HashSet<Continent>,
+    /// ISO2 codes of the selected countries, highlighted in the list.
+    selected: Vec<String>,
+    /// ISO2 code of the country clicked last, where the arrow keys and Shift-click ranges start.
+    anchor: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum CountryListMessage {
     Search(String),
-    FilterOnlyVisited(bool),
+    FilterVisited(VisitFilter),
+    FilterOnlyWishlist(bool),
+    Sort(CountrySort),
+    GroupByContinent(bool),
+    /// Collapses or expands the group of a continent.
+    ToggleGroup(Continent),
+    /// Selects only the given country, or nothing.
     Select(Option<Country>),
+    /// Adds the country to the selection or removes it again.
+    ToggleSelected(Country),
+    /// Replaces the selection, keeping where the last range started.
+    SelectMany(Vec<Country>),
+    AddToSelection(Vec<Country>),
 }
 
 impl CountryList {
     pub fn new() -> Self {
         Self {
             filter: String::new(),
-            filter_only_visited: false,
+            filter_visited: VisitFilter::All,
+            filter_only_wishlist: false,
+            sort: CountrySort::Name,
+            group_by_continent: false,
+            collapsed: HashSet::new(),
+            selected: vec![],
+            anchor: None,
         }
     }
 
+    pub fn view<'a>(&'a self, state: &'a AppState) -> iced::Element<'a, CountryListMessage> {
+        let matching = self.matching_countries(state);
+        let countries = if self.group_by_continent {
+            let mut elements = vec![];
+            for (continent, group) in &matching.into_iter().group_by(|country| Continent::of_iso2(&country.iso2)) {
+                let group: Vec<&Country> = group.collect();
+                elements.push(self.view_group_header(state, continent, &group));
+                if !continent.is_some_and(|continent| self.collapsed.contains(&continent)) {
+                    elements.extend(self.view_rows(state, group));
+                }
+            }
+            elements
+        } else {
+            self.view_rows(state, matching)
+        };
+        let countries = iced::widget::column(countries);
+        let countries_scrollable = iced::widget::scrollable(countries)
+            .id(scrollable::Id::new(LIST_ID))
+            .direction(iced::widget::scrollable::Direction::Vertical(Default::default()));
+        column!(
+            iced::widget::vertical_space(10),
+            countries_scrollable,
+        )
+            .height(iced::Length::Fill)
+            .width(iced::Length::Fixed(250.0))
+            .into()
+    }
+
     #[allow(unstable_name_collisions)]
-    pub fn view(&self) -> iced::Element<'_, CountryListMessage> {
-        let countries = self.get_filtered_countries().iter()
+    fn view_rows<'a>(&self, state: &AppState, countries: Vec<&'a Country>) -> Vec<iced::Element<'a, CountryListMessage>> {
+        countries.into_iter()
             .map(|country| {
                 let null_flag = &Handle::from_memory([]);
                 let flag = FLAGS.get(&country.iso2).unwrap_or(null_flag);
                 let flag = iced::widget::image(flag.clone())
                     .width(iced::Length::Fixed(40.0));
+                let style = if self.selected.contains(&country.iso2) {
+                    iced::theme::Button::Primary
+                } else {
+                    iced::theme::Button::Text
+                };
+                let badge = if state.is_visited(country) { "✓" } else { "" };
                 iced::widget::button(row!(
                     iced::widget::text(&country.name),
                     iced::widget::horizontal_space(iced::Length::Fill),
+                    iced::widget::text(badge).width(iced::Length::Fixed(20.0)),
                     flag,
                     iced::widget::horizontal_space(iced::Length::Fixed(10.0)),
                 ))
                     .on_press(CountryListMessage::Select(Some(country.clone())))
                     .width(iced::Length::Fill)
-                    .style(iced::theme::Button::Text)
+                    .style(style)
                     .into()
             })
             .intersperse_with(|| {
                 iced::widget::horizontal_rule(0).into()
             })
-            .collect::<Vec<iced::Element<'_, CountryListMessage>>>();
-        let countries = iced::widget::column(countries);
-        let countries_scrollable = iced::widget::scrollable(countries)
-            .direction(iced::widget::scrollable::Direction::Vertical(Default::default()));
-        column!(
-            iced::widget::vertical_space(10),
-            countries_scrollable,
-        )
-            .height(iced::Length::Fill)
-            .width(iced::Length::Fixed(250.0))
-            .into()
+            .collect()
+    }
+
+    fn view_group_header<'a>(&self, state: &AppState, continent: Option<Continent>, countries: &[&Country]) -> iced::Element<'a, CountryListMessage> {
+        let visited = countries.iter().filter(|country| state.is_visited(country)).count();
+        let collapsed = continent.is_some_and(|continent| self.collapsed.contains(&continent));
+        let name = continent.map(|continent| continent.to_string()).unwrap_or("Other".to_string());
+        let header = iced::widget::button(row!(
+            iced::widget::text(if collapsed { "▸" } else { "▾" }).width(iced::Length::Fixed(15.0)),
+            iced::widget::text(name),
+            iced::widget::horizontal_space(iced::Length::Fill),
+            iced::widget::text(format!("{}/{}", visited, countries.len())),
+        ))
+            .width(iced::Length::Fill)
+            .style(iced::theme::Button::Secondary);
+        match continent {
+            Some(continent) => header.on_press(CountryListMessage::ToggleGroup(continent)).into(),
+            None => header.into(),
+        }
     }
 
     pub fn update(&mut self, message: CountryListMessage) {
@@ -65,19 +141,126 @@ impl CountryList {
             CountryListMessage::Search(filter) => {
                 self.filter = filter;
             }
-            CountryListMessage::Select(_) => {}
-            CountryListMessage::FilterOnlyVisited(only_visited) => {
-                self.filter_only_visited = only_visited;
+            CountryListMessage::Select(country) => {
+                self.anchor = country.map(|country| country.iso2);
+                self.selected = self.anchor.iter().cloned().collect();
+            }
+            CountryListMessage::ToggleSelected(country) => {
+                if let Some(index) = self.selected.iter().position(|iso2| *iso2 == country.iso2) {
+                    self.selected.remove(index);
+                } else {
+                    self.selected.push(country.iso2.clone());
+                }
+                self.anchor = Some(country.iso2);
+            }
+            CountryListMessage::SelectMany(countries) => {
+                self.selected = countries.into_iter().map(|country| country.iso2).collect();
+            }
+            CountryListMessage::AddToSelection(countries) => {
+                for country in countries {
+                    if !self.selected.contains(&country.iso2) {
+                        self.selected.push(country.iso2);
+                    }
+                }
+            }
+            CountryListMessage::FilterVisited(filter_visited) => {
+                self.filter_visited = filter_visited;
+            }
+            CountryListMessage::Sort(sort) => self.sort = sort,
+            CountryListMessage::GroupByContinent(group_by_continent) => self.group_by_continent = group_by_continent,
+            CountryListMessage::ToggleGroup(continent) => {
+                if !self.collapsed.remove(&continent) {
+                    self.collapsed.insert(continent);
+                }
             }
+            CountryListMessage::FilterOnlyWishlist(only_wishlist) => {
+                self.filter_only_wishlist = only_wishlist;
+            }
+        }
+    }
+
+    /// ISO2 codes of the selected countries, in the order they were selected.
+    pub fn selected(&self) -> &[String] {
+        &self.selected
+    }
+
+    /// The shown countries from the one clicked last up to the given one, for Shift-click.
+    pub fn range_to(&self, state: &AppState, country: &Country) -> Vec<Country> {
+        let countries = self.get_filtered_countries(state);
+        let end = countries.iter().position(|shown| shown.iso2 == country.iso2);
+        match (self.position(&countries), end) {
+            (Some(start), Some(end)) => countries[start.min(end)..=start.max(end)].iter()
+                .map(|country| (*country).clone())
+                .collect(),
+            _ => vec![country.clone()],
         }
     }
 
-    fn get_filtered_countries(&self) -> Vec<Country> {
-        database::all_countries_with_visit_status(&mut database::connection().unwrap()).unwrap().into_iter()
-            .filter(|(country, _visited)| country.matches_filter(&self.filter.to_lowercase()))
-            .filter(|(_country, visited)| !self.filter_only_visited || *visited)
-            .map(|(country, _visited)| country)
-            .sorted_by(|a, b| Ord::cmp(&a.name, &b.name))
+    /// The country `offset` places away from the selected one among the shown countries, or
+    /// the first or last one when nothing shown is selected.
+    pub fn neighbour(&self, state: &AppState, offset: isize) -> Option<Country> {
+        let countries = self.get_filtered_countries(state);
+        let last = countries.len().checked_sub(1)?;
+        let index = match self.position(&countries) {
+            Some(index) => index.saturating_add_signed(offset).min(last),
+            None if offset < 0 => last,
+            None => 0,
+        };
+        Some(countries[index].clone())
+    }
+
+    /// Scrolls the list so that the selected country is visible.
+    pub fn scroll_to_selected<Message: 'static>(&self, state: &AppState) -> Command<Message> {
+        let countries = self.get_filtered_countries(state);
+        let Some(index) = self.position(&countries) else {
+            return Command::none();
+        };
+        let y = index as f32 / countries.len().saturating_sub(1).max(1) as f32;
+        scrollable::snap_to(scrollable::Id::new(LIST_ID), scrollable::RelativeOffset { x: 0.0, y })
+    }
+
+    fn position(&self, countries: &[&Country]) -> Option<usize> {
+        let anchor = self.anchor.as_ref()?;
+        countries.iter().position(|country| &country.iso2 == anchor)
+    }
+
+    /// The countries shown in the list, in the order they are shown.
+    fn get_filtered_countries<'a>(&self, state: &'a AppState) -> Vec<&'a Country> {
+        self.matching_countries(state).into_iter()
+            .filter(|country| !self.group_by_continent
+                || !Continent::of_iso2(&country.iso2).is_some_and(|continent| self.collapsed.contains(&continent)))
             .collect()
     }
+
+    /// The countries passing the filters including the ones in collapsed groups, best search
+    /// matches first and grouped by continent if grouping is on.
+    fn matching_countries<'a>(&self, state: &'a AppState) -> Vec<&'a Country> {
+        state.countries_with_visit_status()
+            .filter(|(_country, visited)| self.filter_visited.accepts(*visited))
+            .filter(|(country, _visited)| !self.filter_only_wishlist || state.wish(country).is_some())
+            .filter_map(|(country, _visited)| country.search_score(&self.filter).map(|score| (country, score)))
+            .sorted_by(|(a, a_score), (b, b_score)| {
+                let group = if self.group_by_continent {
+                    Continent::of_iso2(&a.iso2).cmp(&Continent::of_iso2(&b.iso2))
+                } else {
+                    Ordering::Equal
+                };
+                group.then(a_score.cmp(b_score)).then_with(|| self.compare(state, a, b))
+            })
+            .map(|(country, _score)| country)
+            .collect()
+    }
+
+    fn compare(&self, state: &AppState, a: &Country, b: &Country) -> Ordering {
+        let by_name = || a.name.cmp(&b.name);
+        match self.sort {
+            CountrySort::Name => by_name(),
+            CountrySort::Iso => a.iso2.cmp(&b.iso2),
+            CountrySort::Continent => Continent::of_iso2(&a.iso2).cmp(&Continent::of_iso2(&b.iso2)).then_with(by_name),
+            CountrySort::VisitDate => state.is_visited(b).cmp(&state.is_visited(a))
+                .then(state.visited_on(b).cmp(&state.visited_on(a)))
+                .then_with(by_name),
+            CountrySort::VisitCount => state.visit_count(b).cmp(&state.visit_count(a)).then_with(by_name),
+        }
+    }
 }
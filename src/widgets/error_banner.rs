@@ -0,0 +1,46 @@
+use iced::widget::{column, row};
+
+/// Shows the last error that occurred without interrupting the user, until it is dismissed.
+pub struct ErrorBanner {
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ErrorBannerMessage {
+    Dismiss,
+}
+
+impl ErrorBanner {
+    pub fn new() -> Self {
+        Self {
+            error: None,
+        }
+    }
+
+    pub fn show(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn view(&self) -> iced::Element<'_, ErrorBannerMessage> {
+        if let Some(error) = &self.error {
+            let banner = row!(
+                iced::widget::text(error).width(iced::Length::Fill),
+                iced::widget::button("Dismiss").on_press(ErrorBannerMessage::Dismiss),
+            )
+            .padding(5)
+            .align_items(iced::Alignment::Center);
+            iced::widget::container(banner)
+                .style(iced::theme::Container::Box)
+                .width(iced::Length::Fill)
+                .into()
+        } else {
+            column!().into()
+        }
+    }
+
+    pub fn update(&mut self, msg: ErrorBannerMessage) {
+        match msg {
+            ErrorBannerMessage::Dismiss => self.error = None,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::{Display, Formatter};
+use chrono::{Datelike, Local};
+use iced::widget::{column, row};
+use crate::base_data::Continent;
+use crate::goals::Goal;
+use crate::state::AppState;
+
+/// The goals of the profile with their progress and a form to set new ones, shown in place of
+/// the country info while it is open.
+pub struct GoalsPanel {
+    open: bool,
+    kind: GoalKind,
+    count: String,
+    year: String,
+    continent: Option<Continent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalKind {
+    CountriesInYear,
+    Continent,
+}
+
+impl GoalKind {
+    const ALL: [GoalKind; 2] = [GoalKind::CountriesInYear, GoalKind::Continent];
+}
+
+impl Display for GoalKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoalKind::CountriesInYear => write!(f, "countries in a year"),
+            GoalKind::Continent => write!(f, "complete a continent"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GoalsPanelMessage {
+    Toggle,
+    Kind(GoalKind),
+    Count(String),
+    Year(String),
+    Continent(Continent),
+    Add,
+    Remove(i32),
+}
+
+impl GoalsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            kind: GoalKind::CountriesInYear,
+            count: "5".to_string(),
+            year: (Local::now().year() + 1).to_string(),
+            continent: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// The goal entered in the form, `None` while it is incomplete or the count is out of the
+    /// range the database can store.
+    pub fn new_goal(&self) -> Option<Goal> {
+        match self.kind {
+            GoalKind::CountriesInYear => Some(Goal::CountriesInYear {
+                count: self.count.parse().ok().filter(|count| *count > 0 && i32::try_from(*count).is_ok())?,
+                year: self.year.parse().ok()?,
+            }),
+            GoalKind::Continent => self.continent.map(Goal::Continent),
+        }
+    }
+
+    pub fn view_toggle(&self) -> iced::Element<'_, GoalsPanelMessage> {
+        let label = if self.open { "Close goals" } else { "Goals" };
+        iced::widget::button(label)
+            .on_press(GoalsPanelMessage::Toggle)
+            .into()
+    }
+
+    pub fn view(&self, state: &AppState) -> iced::Element<'_, GoalsPanelMessage> {
+        let goals = state.goals().iter()
+            .map(|(id, goal)| {
+                let (reached, needed) = state.goal_progress(goal);
+                let done = if needed > 0 && reached >= needed { " - done" } else { "" };
+                column!(
+                    row!(
+                        iced::widget::text(goal.to_string()).width(iced::Length::Fill),
+                        iced::widget::button(iced::widget::text("Remove").size(12)).on_press(GoalsPanelMessage::Remove(*id)),
+                    )
+                    .align_items(iced::Alignment::Center),
+                    iced::widget::progress_bar(0.0..=needed.max(1) as f32, reached as f32).height(iced::Length::Fixed(8.0)),
+                    iced::widget::text(format!("{} of {}{}", reached, needed, done)).size(12),
+                ).into()
+            })
+            .collect();
+        let kind = iced::widget::pick_list(&GoalKind::ALL[..], Some(self.kind), GoalsPanelMessage::Kind);
+        let details: iced::Element<'_, GoalsPanelMessage> = match self.kind {
+            GoalKind::CountriesInYear => row!(
+                iced::widget::text_input("count", &self.count)
+                    .on_input(GoalsPanelMessage::Count)
+                    .width(iced::Length::Fixed(50.0)),
+                iced::widget::text("in"),
+                iced::widget::text_input("year", &self.year)
+                    .on_input(GoalsPanelMessage::Year)
+                    .width(iced::Length::Fixed(70.0)),
+            )
+            .spacing(5)
+            .align_items(iced::Alignment::Center)
+            .into(),
+            GoalKind::Continent => iced::widget::pick_list(&Continent::ALL[..], self.continent, GoalsPanelMessage::Continent)
+                .placeholder("continent")
+                .into(),
+        };
+        let add = iced::widget::button("Add goal")
+            .on_press_maybe(self.new_goal().map(|_| GoalsPanelMessage::Add));
+        let panel = column!(
+            iced::widget::text("Goals").size(25),
+            iced::widget::column(goals).spacing(10),
+            iced::widget::horizontal_rule(10),
+            iced::widget::text("New goal"),
+            kind,
+            details,
+            add,
+        )
+        .spacing(5)
+        .padding(5);
+        iced::widget::scrollable(panel)
+            .width(iced::Length::Fixed(250.0))
+            .into()
+    }
+
+    pub fn update(&mut self, msg: GoalsPanelMessage) {
+        match msg {
+            GoalsPanelMessage::Toggle => self.open = !self.open,
+            GoalsPanelMessage::Kind(kind) => self.kind = kind,
+            GoalsPanelMessage::Count(count) if count.chars().all(|c| c.is_ascii_digit()) => self.count = count,
+            GoalsPanelMessage::Year(year) if year.len() <= 4 && year.chars().all(|c| c.is_ascii_digit()) => self.year = year,
+            GoalsPanelMessage::Continent(continent) => self.continent = Some(continent),
+            GoalsPanelMessage::Count(_) | GoalsPanelMessage::Year(_) | GoalsPanelMessage::Add | GoalsPanelMessage::Remove(_) => {}
+        }
+    }
+}
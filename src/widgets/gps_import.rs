@@ -0,0 +1,55 @@
+use iced::widget::row;
+
+/// Starts an import of GPS data from a GPX file or a directory of photos from the toolbar, which is reviewed before anything is written.
+pub struct GpsImport {
+    path: String,
+    /// Whether an import is being read, which can take a while for long tracks.
+    reading: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum GpsImportMessage {
+    Path(String),
+    ImportGpx,
+    ImportPhotos,
+}
+
+impl GpsImport {
+    pub fn new() -> Self {
+        Self {
+            path: String::new(),
+            reading: false,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.trim()
+    }
+
+    pub fn finished(&mut self) {
+        self.reading = false;
+    }
+
+    pub fn view(&self) -> iced::Element<'_, GpsImportMessage> {
+        let ready = !self.reading && !self.path().is_empty();
+        let path = iced::widget::text_input("GPX file or photo directory", &self.path)
+            .on_input(GpsImportMessage::Path)
+            .width(iced::Length::Fixed(200.0));
+        let (gpx_label, photos_label) = if self.reading { ("Reading...", "Reading...") } else { ("Import GPX", "Import photos") };
+        row!(
+            path,
+            iced::widget::button(gpx_label).on_press_maybe(ready.then_some(GpsImportMessage::ImportGpx)),
+            iced::widget::button(photos_label).on_press_maybe(ready.then_some(GpsImportMessage::ImportPhotos)),
+        )
+        .spacing(5)
+        .align_items(iced::Alignment::Center)
+        .into()
+    }
+
+    pub fn update(&mut self, msg: GpsImportMessage) {
+        match msg {
+            GpsImportMessage::Path(path) => self.path = path,
+            GpsImportMessage::ImportGpx | GpsImportMessage::ImportPhotos => self.reading = true,
+        }
+    }
+}
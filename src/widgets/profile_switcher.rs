@@ -0,0 +1,82 @@
+use iced::widget::{column, row};
+use crate::models::Profile;
+use crate::state::AppState;
+
+pub struct ProfileSwitcher {
+    new_profile_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProfileSwitcherMessage {
+    Select(Profile),
+    NewProfileName(String),
+    Create,
+    CompareWith(Profile),
+    StopComparing,
+}
+
+impl ProfileSwitcher {
+    pub fn new() -> Self {
+        Self {
+            new_profile_name: String::new(),
+        }
+    }
+
+    /// Name of the profile that should be created, if the user entered one.
+    pub fn new_profile_name(&self) -> Option<&str> {
+        Some(self.new_profile_name.trim()).filter(|name| !name.is_empty())
+    }
+
+    pub fn view(&self, state: &AppState) -> iced::Element<'_, ProfileSwitcherMessage> {
+        let profiles = iced::widget::pick_list(
+            state.profiles().to_vec(),
+            Some(state.profile().clone()),
+            ProfileSwitcherMessage::Select,
+        )
+        .width(iced::Length::Fill);
+        let new_profile = iced::widget::text_input("New profile", &self.new_profile_name)
+            .on_input(ProfileSwitcherMessage::NewProfileName)
+            .on_submit(ProfileSwitcherMessage::Create);
+        let other_profiles = state.profiles().iter()
+            .filter(|profile| *profile != state.profile())
+            .cloned()
+            .collect::<Vec<Profile>>();
+        let compared_profile = state.compared_with()
+            .and_then(|compared_with| other_profiles.iter().find(|profile| profile.name == compared_with.name))
+            .cloned();
+        let compare_with = iced::widget::pick_list(
+            other_profiles,
+            compared_profile,
+            ProfileSwitcherMessage::CompareWith,
+        )
+        .placeholder("Compare with...")
+        .width(iced::Length::Fill);
+        let mut stop_comparing = iced::widget::button("Stop");
+        if state.compared_with().is_some() {
+            stop_comparing = stop_comparing.on_press(ProfileSwitcherMessage::StopComparing);
+        }
+        column!(
+            profiles,
+            row!(
+                new_profile,
+                iced::widget::button("Add").on_press(ProfileSwitcherMessage::Create),
+            ),
+            row!(
+                compare_with,
+                stop_comparing,
+            ),
+        )
+        .width(iced::Length::Fixed(250.0))
+        .into()
+    }
+
+    pub fn update(&mut self, msg: ProfileSwitcherMessage) {
+        match msg {
+            ProfileSwitcherMessage::Select(_)
+            | ProfileSwitcherMessage::CompareWith(_)
+            | ProfileSwitcherMessage::StopComparing => {}
+            ProfileSwitcherMessage::NewProfileName(name) => self.new_profile_name = name,
+            ProfileSwitcherMessage::Create => self.new_profile_name.clear(),
+        }
+    }
+}
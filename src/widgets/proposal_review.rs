@@ -0,0 +1,74 @@
+use iced::widget::{checkbox, column, row};
+use crate::state::AppState;
+use crate::visit_proposals::VisitProposal;
+
+/// Countries found in imported GPS data, shown in place of the country info until the user
+/// accepts the checked ones or discards them all.
+pub struct ProposalReview {
+    proposals: Vec<(VisitProposal, bool)>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProposalReviewMessage {
+    Check(usize, bool),
+    Accept,
+    Discard,
+}
+
+impl ProposalReview {
+    pub fn new(proposals: Vec<VisitProposal>) -> Self {
+        Self {
+            proposals: proposals.into_iter().map(|proposal| (proposal, true)).collect(),
+        }
+    }
+
+    pub fn accepted(&self) -> Vec<VisitProposal> {
+        self.proposals.iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(proposal, _)| proposal.clone())
+            .collect()
+    }
+
+    pub fn view(&self, state: &AppState) -> iced::Element<'_, ProposalReviewMessage> {
+        let proposals = self.proposals.iter()
+            .enumerate()
+            .map(|(index, (proposal, checked))| {
+                let dates = match (proposal.first, proposal.last) {
+                    (Some(first), Some(last)) if first == last => first.to_string(),
+                    (Some(first), Some(last)) => format!("{} to {}", first, last),
+                    _ => "no date".to_string(),
+                };
+                let visited = if state.is_visited(&proposal.country) { ", visited before" } else { "" };
+                column!(
+                    checkbox(&proposal.country.name, *checked, move |checked| ProposalReviewMessage::Check(index, checked)),
+                    iced::widget::text(format!("{}, {} points{}", dates, proposal.points, visited)).size(12),
+                ).into()
+            })
+            .collect();
+        let count = self.proposals.iter().filter(|(_, checked)| *checked).count();
+        let accept = iced::widget::button(iced::widget::text(format!("Mark {} visited", count)))
+            .on_press_maybe((count > 0).then_some(ProposalReviewMessage::Accept));
+        let panel = column!(
+            iced::widget::text("Found in import").size(25),
+            iced::widget::text(format!("{} countries, uncheck the ones not to mark", self.proposals.len())).size(12),
+            iced::widget::column(proposals).spacing(5),
+            row!(accept, iced::widget::button("Discard").on_press(ProposalReviewMessage::Discard)).spacing(5),
+        )
+        .spacing(5)
+        .padding(5);
+        iced::widget::scrollable(panel)
+            .width(iced::Length::Fixed(250.0))
+            .into()
+    }
+
+    pub fn update(&mut self, msg: ProposalReviewMessage) {
+        match msg {
+            ProposalReviewMessage::Check(index, checked) => {
+                if let Some((_, current)) = self.proposals.get_mut(index) {
+                    *current = checked;
+                }
+            }
+            ProposalReviewMessage::Accept | ProposalReviewMessage::Discard => {}
+        }
+    }
+}
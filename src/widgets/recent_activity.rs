@@ -0,0 +1,92 @@
+use chrono::{Local, TimeZone};
+use iced::widget::{column, row};
+use crate::models::{ChangeLogEntry, Country, GoalEntry, Operation, VisitKind, Wish, WishlistStatus};
+
+/// Read-only list of the latest entries of the change log.
+pub struct RecentActivity {
+    changes: Vec<(ChangeLogEntry, Option<Country>)>,
+}
+
+impl RecentActivity {
+    pub fn new() -> Self {
+        Self {
+            changes: vec![],
+        }
+    }
+
+    pub fn set_changes(&mut self, changes: Vec<(ChangeLogEntry, Option<Country>)>) {
+        self.changes = changes;
+    }
+
+    pub fn view<Message: 'static>(&self) -> iced::Element<'_, Message> {
+        let changes = self.changes.iter()
+            .map(|(entry, country)| {
+                let changed_at = Local.from_utc_datetime(&entry.changed_at)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string();
+                row!(
+                    iced::widget::text(changed_at).width(iced::Length::Fixed(130.0)),
+                    iced::widget::text(describe_change(entry, country.as_ref())).width(iced::Length::Fill),
+                    iced::widget::text(&entry.source).size(12),
+                )
+                .spacing(10)
+                .into()
+            })
+            .collect::<Vec<iced::Element<'_, Message>>>();
+        column!(
+            iced::widget::text("Recent activity").size(18),
+            iced::widget::scrollable(iced::widget::column(changes))
+                .height(iced::Length::Fill),
+        )
+        .padding(5)
+        .height(iced::Length::Fixed(160.0))
+        .into()
+    }
+}
+
+fn describe_change(entry: &ChangeLogEntry, country: Option<&Country>) -> String {
+    let name = country.map(|country| country.name.as_str()).unwrap_or_default();
+    let new_value = entry.new_value.as_deref();
+    match Operation::parse(&entry.operation) {
+        Some(Operation::Visit) => format!("Visited {}", name),
+        Some(Operation::Unvisit) => format!("Unvisited {}", name),
+        Some(Operation::ImportCountry) => format!("Imported {}", name),
+        Some(Operation::Undo) => match new_value {
+            Some("true") => format!("Undo: visited {} again", name),
+            Some("false") => format!("Undo: unvisited {}", name),
+            _ if country.is_some() => format!("Undo: change to {}", name),
+            _ => "Undo: goal change".to_string(),
+        },
+        Some(Operation::Redo) => match new_value {
+            Some("true") => format!("Redo: visited {}", name),
+            Some("false") => format!("Redo: unvisited {}", name),
+            _ if country.is_some() => format!("Redo: change to {}", name),
+            _ => "Redo: goal change".to_string(),
+        },
+        Some(Operation::SetVisitKind) => match new_value.and_then(VisitKind::parse) {
+            Some(kind) => format!("Marked {} as {}", name, kind),
+            None => format!("Changed the visit kind of {}", name),
+        },
+        Some(Operation::SetVisitDate) => match new_value {
+            Some(date) => format!("Dated the visit to {} {}", name, date),
+            None => format!("Removed the visit date of {}", name),
+        },
+        Some(Operation::SetWish) => match new_value.and_then(Wish::from_log_value).map(|wish| wish.status) {
+            Some(WishlistStatus::Planned) => format!("Planned a trip to {}", name),
+            Some(WishlistStatus::Wishlist) => format!("Put {} on the wishlist", name),
+            None => format!("Took {} off the wishlist", name),
+        },
+        Some(Operation::SetDescription) if new_value.is_some() => format!("Described {}", name),
+        Some(Operation::SetDescription) => format!("Removed the description of {}", name),
+        Some(Operation::AddGoal) => format!("Set goal: {}", describe_goal(new_value)),
+        Some(Operation::RemoveGoal) => format!("Removed goal: {}", describe_goal(entry.old_value.as_deref())),
+        None => format!("{} {}", entry.operation, name),
+    }
+}
+
+fn describe_goal(value: Option<&str>) -> String {
+    value.and_then(GoalEntry::from_log_value)
+        .and_then(|entry| entry.goal())
+        .map(|goal| goal.to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,72 @@
+use iced::widget::row;
+
+/// Creates the HTML travel report from the toolbar.
+pub struct ReportExport {
+    path: String,
+    year: String,
+    /// Where the report was last written to.
+    created: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ReportExportMessage {
+    Path(String),
+    Year(String),
+    Create,
+}
+
+impl ReportExport {
+    pub fn new() -> Self {
+        Self {
+            path: "travel_report.html".to_string(),
+            year: String::new(),
+            created: None,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The year the report is limited to, `None` for all years.
+    pub fn year(&self) -> Option<i32> {
+        self.year.parse().ok()
+    }
+
+    pub fn created(&mut self, path: String) {
+        self.created = Some(path);
+    }
+
+    pub fn view(&self) -> iced::Element<'_, ReportExportMessage> {
+        let path = iced::widget::text_input("report file", &self.path)
+            .on_input(ReportExportMessage::Path)
+            .on_submit(ReportExportMessage::Create)
+            .width(iced::Length::Fixed(200.0));
+        let year = iced::widget::text_input("all years", &self.year)
+            .on_input(ReportExportMessage::Year)
+            .on_submit(ReportExportMessage::Create)
+            .width(iced::Length::Fixed(80.0));
+        let created = self.created.as_ref()
+            .map(|path| format!("written to {}", path))
+            .unwrap_or_default();
+        row!(
+            path,
+            year,
+            iced::widget::button("Create report").on_press(ReportExportMessage::Create),
+            iced::widget::text(created).size(12),
+        )
+        .spacing(5)
+        .align_items(iced::Alignment::Center)
+        .into()
+    }
+
+    pub fn update(&mut self, msg: ReportExportMessage) {
+        match msg {
+            ReportExportMessage::Path(path) => self.path = path,
+            ReportExportMessage::Year(year) if year.len() <= 4 && year.chars().all(|c| c.is_ascii_digit()) => {
+                self.year = year;
+            }
+            ReportExportMessage::Year(_) | ReportExportMessage::Create => {}
+        }
+    }
+}
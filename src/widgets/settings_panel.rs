@@ -0,0 +1,106 @@
+use iced::widget::{checkbox, column, row};
+use crate::color_scheme::{ColorScheme, MapColors};
+use crate::keymap::Action;
+use crate::settings::{AppTheme, Settings};
+
+/// Editor for the settings file, shown in place of the country info while it is open.
+pub struct SettingsPanel {
+    open: bool,
+    /// Database path while it is being edited, `None` shows the stored one.
+    database_path: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingsPanelMessage {
+    Toggle,
+    Theme(AppTheme),
+    MapColors(MapColors),
+    DatabasePath(String),
+    SubmitDatabasePath,
+}
+
+impl SettingsPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            database_path: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// The edited database path, an empty path means the default database.
+    pub fn database_path(&self) -> Option<Option<String>> {
+        self.database_path.as_ref()
+            .map(|path| Some(path.trim().to_string()).filter(|path| !path.is_empty()))
+    }
+
+    pub fn view_toggle(&self) -> iced::Element<'_, SettingsPanelMessage> {
+        let label = if self.open { "Close settings" } else { "Settings" };
+        iced::widget::button(label)
+            .on_press(SettingsPanelMessage::Toggle)
+            .into()
+    }
+
+    pub fn view<'a>(&'a self, settings: &'a Settings) -> iced::Element<'a, SettingsPanelMessage> {
+        let map_colors = settings.map_colors;
+        let theme = iced::widget::pick_list(&AppTheme::ALL[..], Some(settings.theme), SettingsPanelMessage::Theme);
+        let scheme = iced::widget::pick_list(
+            &ColorScheme::ALL[..],
+            Some(map_colors.scheme),
+            move |scheme| SettingsPanelMessage::MapColors(MapColors { scheme, ..map_colors }),
+        );
+        let color_blind_safe = checkbox("colour-blind safe", map_colors.color_blind_safe, move |color_blind_safe| {
+            SettingsPanelMessage::MapColors(MapColors { color_blind_safe, ..map_colors })
+        });
+        let database_path = self.database_path.clone()
+            .or(settings.database_path.clone())
+            .unwrap_or_default();
+        let database_path = iced::widget::text_input("~/.country_logger/da.sqlite", &database_path)
+            .on_input(SettingsPanelMessage::DatabasePath)
+            .on_submit(SettingsPanelMessage::SubmitDatabasePath);
+        let window = format!("{} x {}", settings.window.width, settings.window.height);
+        let keys = Action::ALL.into_iter()
+            .map(|action| {
+                let keys: Vec<String> = settings.key_bindings.keys(action).iter().map(ToString::to_string).collect();
+                row!(
+                    iced::widget::text(action).size(14).width(iced::Length::Fixed(110.0)),
+                    iced::widget::text(keys.join(", ")).size(14),
+                ).into()
+            })
+            .collect();
+        let panel = column!(
+            iced::widget::text("Settings").size(25),
+            row!(iced::widget::text("theme"), theme).spacing(5),
+            row!(iced::widget::text("map colours"), scheme).spacing(5),
+            color_blind_safe,
+            iced::widget::text("default database"),
+            database_path,
+            iced::widget::text("used from the next start, press enter to save").size(12),
+            row!(iced::widget::text("window"), iced::widget::text(window)).spacing(5),
+            iced::widget::text("window, filters and selection are saved on exit").size(12),
+            iced::widget::text("keys"),
+            iced::widget::column(keys),
+            iced::widget::text("rebind them under key_bindings in the settings file").size(12),
+        )
+        .spacing(5)
+        .padding(5);
+        iced::widget::scrollable(panel)
+            .width(iced::Length::Fixed(250.0))
+            .into()
+    }
+
+    pub fn update(&mut self, msg: SettingsPanelMessage) {
+        match msg {
+            SettingsPanelMessage::Toggle => {
+                self.open = !self.open;
+                self.database_path = None;
+            }
+            SettingsPanelMessage::DatabasePath(path) => self.database_path = Some(path),
+            SettingsPanelMessage::SubmitDatabasePath => self.database_path = None,
+            SettingsPanelMessage::Theme(_) | SettingsPanelMessage::MapColors(_) => {}
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use iced::widget::{checkbox, column};
+use crate::state::AppState;
+
+/// Summary of how much of the world the active profile has seen.
+pub struct Statistics {
+    include_transit: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum StatisticsMessage {
+    IncludeTransit(bool),
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        Self {
+            include_transit: false,
+        }
+    }
+
+    pub fn view(&self, state: &AppState) -> iced::Element<'_, StatisticsMessage> {
+        let visited = state.visited_count(self.include_transit);
+        let total = state.country_count().max(1);
+        let summary = format!("{} of {} countries ({:.1}%)", visited, total, visited as f32 * 100.0 / total as f32);
+        column!(
+            iced::widget::text(summary),
+            checkbox("count transit", self.include_transit, StatisticsMessage::IncludeTransit),
+        )
+        .width(iced::Length::Fixed(250.0))
+        .into()
+    }
+
+    pub fn update(&mut self, msg: StatisticsMessage) {
+        match msg {
+            StatisticsMessage::IncludeTransit(include_transit) => self.include_transit = include_transit,
+        }
+    }
+}
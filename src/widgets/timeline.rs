@@ -0,0 +1,124 @@
+use chrono::{Duration, Local, NaiveDate};
+use iced::time::Instant;
+use iced::widget::row;
+use crate::state::AppState;
+
+/// How long replaying the whole timeline takes, in seconds.
+const REPLAY_SECONDS: f64 = 10.0;
+
+/// Slider under the map that shows the map as it was on an earlier day, and replays how it
+/// filled in.
+pub struct Timeline {
+    /// Shown day as days since the start of the timeline, `None` shows today.
+    day: Option<f64>,
+    playing: bool,
+    last_frame: Option<Instant>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TimelineMessage {
+    Seek(f64),
+    Play,
+    Pause,
+    Today,
+    Frame(Instant),
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            day: None,
+            playing: false,
+            last_frame: None,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// The shown day, `None` if the map shows today.
+    pub fn as_of(&self, state: &AppState) -> Option<NaiveDate> {
+        let (start, _) = range(state)?;
+        self.day.map(|day| start + Duration::days(day as i64))
+    }
+
+    pub fn view(&self, state: &AppState) -> iced::Element<'_, TimelineMessage> {
+        let Some((start, end)) = range(state) else {
+            return iced::widget::text("Visits with a date show up on a timeline here").size(14).into();
+        };
+        let days = (end - start).num_days() as f64;
+        let play = if self.playing {
+            iced::widget::button("Pause").on_press(TimelineMessage::Pause)
+        } else {
+            iced::widget::button("Play").on_press(TimelineMessage::Play)
+        };
+        let slider = iced::widget::slider(0.0..=days, self.day.unwrap_or(days), TimelineMessage::Seek)
+            .step(1.0);
+        let shown = match self.as_of(state) {
+            Some(date) => date.to_string(),
+            None => "today".to_string(),
+        };
+        let mut today = iced::widget::button("Today");
+        if self.day.is_some() {
+            today = today.on_press(TimelineMessage::Today);
+        }
+        row!(
+            play,
+            slider,
+            iced::widget::text(shown).width(iced::Length::Fixed(90.0)),
+            today,
+        )
+        .spacing(10)
+        .padding(5)
+        .align_items(iced::Alignment::Center)
+        .into()
+    }
+
+    pub fn update(&mut self, msg: TimelineMessage, state: &AppState) {
+        let Some((start, end)) = range(state) else {
+            *self = Timeline::new();
+            return;
+        };
+        let days = (end - start).num_days() as f64;
+        match msg {
+            TimelineMessage::Seek(day) => {
+                self.playing = false;
+                self.day = Some(day).filter(|day| *day < days);
+            }
+            TimelineMessage::Play => {
+                self.playing = true;
+                self.last_frame = None;
+                if self.day.is_none() {
+                    self.day = Some(0.0);
+                }
+            }
+            TimelineMessage::Pause => self.playing = false,
+            TimelineMessage::Today => {
+                self.playing = false;
+                self.day = None;
+            }
+            TimelineMessage::Frame(now) => {
+                if !self.playing {
+                    return;
+                }
+                let elapsed = self.last_frame.map(|last| (now - last).as_secs_f64()).unwrap_or_default();
+                let day = self.day.unwrap_or_default() + elapsed * days.max(1.0) / REPLAY_SECONDS;
+                self.last_frame = Some(now);
+                if day >= days {
+                    self.playing = false;
+                    self.day = None;
+                } else {
+                    self.day = Some(day);
+                }
+            }
+        }
+    }
+}
+
+/// From the day before the first dated visit, when only visits without a date are shown, to
+/// today.
+fn range(state: &AppState) -> Option<(NaiveDate, NaiveDate)> {
+    let (first, last) = state.visit_date_range()?;
+    Some((first - Duration::days(1), last.max(Local::now().date_naive())))
+}
@@ -1,9 +1,17 @@
-use iced::{Color, Rectangle};
+use chrono::NaiveDate;
+use iced::{Color, Rectangle, Size};
+use iced::event::Status;
+use iced::keyboard::Modifiers;
 use iced::mouse::Cursor;
 use iced::widget::canvas;
-use iced::widget::canvas::{Fill, fill, Frame, Path, Stroke, stroke};
-use crate::database;
-use crate::models::Country;
+use iced::widget::{checkbox, column, row};
+use iced::widget::canvas::{Fill, fill, Frame, Path, Stroke, stroke, Text};
+use crate::adjacency::graph_color;
+use crate::base_data::Continent;
+use crate::color_scheme::{ColorScheme, MapColors};
+use crate::compare::ComparisonSide;
+use crate::models::{Country, VisitKind};
+use crate::state::AppState;
 use crate::svg_helper::{COUNTRY_POLYGONS, Point, Polygon, SVG_HEIGT, SVG_WIDTH};
 
 #[derive(Debug)]
@@ -23,53 +31,210 @@ impl WorldMapCountryFilter {
 pub enum WorldMapMessage {
     FilterChanged(WorldMapCountryFilter),
     FilterRemoved,
+    VisitsChanged,
+    WishlistChanged,
+    ComparisonChanged,
+    ColorsChanged(MapColors),
+    ThemeChanged,
+    /// Shows the visits as they were on the given day, `None` shows all of them.
+    ShowAsOf(Option<NaiveDate>),
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    /// Moves the visible part of the map by fractions of its width and height.
+    Pan(f32, f32),
+    /// ISO2 codes of the countries in a box dragged on the map, and whether they are added to
+    /// the selection rather than replacing it.
+    BoxSelected(Vec<String>, bool),
 }
 
 pub struct WorldMap {
     country_filter: Option<WorldMapCountryFilter>,
-    countries: Vec<(Country, bool)>,
+    map_colors: MapColors,
+    as_of: Option<NaiveDate>,
+    viewport: Viewport,
     countries_cache: canvas::Cache,
 }
 
+const ZOOM_STEP: f32 = 1.5;
+const MAX_ZOOM: f32 = 16.0;
+
+/// The part of the map that is shown.
+#[derive(Debug, Clone, Copy)]
+struct Viewport {
+    zoom: f32,
+    /// Shown centre of the map, relative to its width and height.
+    center: (f32, f32),
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            center: (0.5, 0.5),
+        }
+    }
+}
+
+impl Viewport {
+    fn zoomed(self, factor: f32) -> Self {
+        Viewport { zoom: (self.zoom * factor).clamp(1.0, MAX_ZOOM), ..self }.clamped()
+    }
+
+    fn panned(self, dx: f32, dy: f32) -> Self {
+        let center = (self.center.0 + dx / self.zoom, self.center.1 + dy / self.zoom);
+        Viewport { center, ..self }.clamped()
+    }
+
+    /// Size and position of the whole map when drawn into a frame of the given size.
+    fn layout(&self, frame: Size) -> (Point, Point) {
+        let aspect_ratio_svg = SVG_WIDTH / SVG_HEIGT;
+        let aspect_ratio_frame = frame.width / frame.height;
+        let fitted_size = if aspect_ratio_svg > aspect_ratio_frame {
+            Point(frame.width, frame.width / aspect_ratio_svg)
+        } else {
+            Point(frame.height * aspect_ratio_svg, frame.height)
+        };
+        let size = Point(fitted_size.0 * self.zoom, fitted_size.1 * self.zoom);
+        let offset = Point(
+            frame.width / 2.0 - self.center.0 * size.0,
+            frame.height / 2.0 - self.center.1 * size.1,
+        );
+        (size, offset)
+    }
+
+    /// The point of the map, relative to its width and height, under a point of the frame.
+    fn map_point(self, frame: Size, point: iced::Point) -> Point {
+        let (size, offset) = self.layout(frame);
+        Point((point.x - offset.0) / size.0, (point.y - offset.1) / size.1)
+    }
+
+    /// Keeps the map filling the view, so panning never shows more than its edge.
+    fn clamped(self) -> Self {
+        let half = 0.5 / self.zoom;
+        let center = (self.center.0.clamp(half, 1.0 - half), self.center.1.clamp(half, 1.0 - half));
+        Viewport { center, ..self }
+    }
+}
+
+/// The map together with the state it renders, borrowed for the duration of a single view.
+struct WorldMapCanvas<'a> {
+    world_map: &'a WorldMap,
+    state: &'a AppState,
+}
+
 impl WorldMap {
-    pub fn new() -> Self {
+    pub fn new(map_colors: MapColors) -> Self {
         Self {
             country_filter: None,
-            countries: database::all_countries_with_visit_status(&mut database::require_connection()).unwrap(),
+            map_colors,
+            as_of: None,
+            viewport: Viewport::default(),
             countries_cache: canvas::Cache::default(),
         }
     }
 
-    pub fn view(&self) -> iced::Element<'_, WorldMapMessage> {
-        canvas(self)
+    pub fn map_colors(&self) -> MapColors {
+        self.map_colors
+    }
+
+    pub fn view<'a>(&'a self, state: &'a AppState) -> iced::Element<'a, WorldMapMessage> {
+        let map_colors = self.map_colors;
+        let scheme = iced::widget::pick_list(
+            &ColorScheme::ALL[..],
+            Some(map_colors.scheme),
+            move |scheme| WorldMapMessage::ColorsChanged(MapColors { scheme, ..map_colors }),
+        );
+        let color_blind_safe = checkbox("colour-blind safe", map_colors.color_blind_safe, move |color_blind_safe| {
+            WorldMapMessage::ColorsChanged(MapColors { color_blind_safe, ..map_colors })
+        });
+        let map = canvas(WorldMapCanvas { world_map: self, state })
             .height(iced::Length::Fill)
-            .width(iced::Length::Fill)
-            .into()
+            .width(iced::Length::Fill);
+        column!(
+            row!(scheme, color_blind_safe)
+                .spacing(10)
+                .padding(5)
+                .align_items(iced::Alignment::Center),
+            map,
+        ).into()
     }
 
     pub fn update(&mut self, msg: WorldMapMessage) {
-        self.countries = database::all_countries_with_visit_status(&mut database::require_connection()).unwrap();
         self.countries_cache.clear();
         match msg {
             WorldMapMessage::FilterChanged(filter) => self.country_filter = Some(filter),
             WorldMapMessage::FilterRemoved => self.country_filter = None,
+            WorldMapMessage::ColorsChanged(map_colors) => self.map_colors = map_colors,
+            WorldMapMessage::ShowAsOf(as_of) => self.as_of = as_of,
+            WorldMapMessage::VisitsChanged
+            | WorldMapMessage::WishlistChanged
+            | WorldMapMessage::ComparisonChanged
+            | WorldMapMessage::ThemeChanged => {}
+            WorldMapMessage::ZoomIn => self.viewport = self.viewport.zoomed(ZOOM_STEP),
+            WorldMapMessage::ZoomOut => self.viewport = self.viewport.zoomed(1.0 / ZOOM_STEP),
+            WorldMapMessage::ResetZoom => self.viewport = Viewport::default(),
+            WorldMapMessage::Pan(dx, dy) => self.viewport = self.viewport.panned(dx, dy),
+            WorldMapMessage::BoxSelected(..) => {}
         }
     }
 }
 
 enum CountryRenderStyle {
-    Normal(bool),
+    Normal(Option<VisitKind>),
+    Filled(Color),
+    Compared(Option<ComparisonSide>),
     Selected,
     Unselected,
 }
 
+/// Colours of the map that follow the light or dark theme rather than the colour scheme.
+struct MapStyle {
+    ocean: Color,
+    border: Color,
+    selected_border: Color,
+    unselected: Color,
+    unvisited: Color,
+    legend_background: Color,
+    legend_text: Color,
+}
+
+impl MapStyle {
+    fn of(theme: &iced::Theme) -> Self {
+        let palette = theme.palette();
+        let background = palette.background;
+        let legend_background = Color { a: 0.8, ..background };
+        if (background.r + background.g + background.b) / 3.0 < 0.5 {
+            Self {
+                ocean: Color::from_rgb8(0x1A, 0x27, 0x38),
+                border: Color::from_rgb(0.1, 0.1, 0.12),
+                selected_border: Color::from_rgb(0.4, 1.0, 0.5),
+                unselected: Color::from_rgb(0.3, 0.3, 0.3),
+                unvisited: Color::from_rgb(0.45, 0.45, 0.45),
+                legend_background,
+                legend_text: palette.text,
+            }
+        } else {
+            Self {
+                ocean: Color::from_rgb8(0xD6, 0xE8, 0xF5),
+                border: Color::BLACK,
+                selected_border: Color::from_rgb(0.0, 1.0, 0.0),
+                unselected: Color::from_rgb(0.7, 0.7, 0.7),
+                unvisited: Color::from_rgb(0.85, 0.85, 0.85),
+                legend_background,
+                legend_text: palette.text,
+            }
+        }
+    }
+}
+
 impl CountryRenderStyle {
-    fn get_fill_color(&self, country: &Country) -> Color {
+    fn get_fill_color(&self, country: &Country, map_style: &MapStyle) -> Color {
         match self {
-            CountryRenderStyle::Normal(visited) => {
+            CountryRenderStyle::Normal(visit_kind) => {
                 let iso3_bytes = country.iso3.as_bytes();
-                if *visited {
-                    color_from_ascii(iso3_bytes)
+                if let Some(visit_kind) = visit_kind {
+                    with_visit_kind_intensity(color_from_ascii(iso3_bytes), *visit_kind)
                 } else {
                     let iso3_bytes: Vec<u8> = iso3_bytes.iter()
                         .map(|c| {
@@ -80,31 +245,59 @@ impl CountryRenderStyle {
                     color_from_ascii(&iso3_bytes)
                 }
             }
+            CountryRenderStyle::Filled(color) => *color,
+            CountryRenderStyle::Compared(Some(side)) => comparison_color(*side),
+            CountryRenderStyle::Compared(None) => map_style.unvisited,
             CountryRenderStyle::Selected => {
                 let iso3_bytes = country.iso3.as_bytes();
                 color_from_ascii(iso3_bytes)
             }
-            CountryRenderStyle::Unselected => {
-                Color::from_rgb(0.7, 0.7, 0.7)
-            }
+            CountryRenderStyle::Unselected => map_style.unselected,
         }
     }
 
-    fn get_stroke_style(&self) -> stroke::Style {
+    fn get_stroke_style(&self, map_style: &MapStyle) -> stroke::Style {
         match self {
-            CountryRenderStyle::Normal(_) => {
-                stroke::Style::Solid(Color::from_rgb(0.0, 0.0, 0.0))
+            CountryRenderStyle::Normal(_) | CountryRenderStyle::Filled(_) | CountryRenderStyle::Compared(_) => {
+                stroke::Style::Solid(map_style.border)
             }
             CountryRenderStyle::Selected => {
-                stroke::Style::Solid(Color::from_rgb(0.0, 1.0, 0.0))
+                stroke::Style::Solid(map_style.selected_border)
             }
             CountryRenderStyle::Unselected => {
-                stroke::Style::Solid(Color::from_rgb(0.0, 0.0, 0.0))
+                stroke::Style::Solid(map_style.border)
             }
         }
     }
 }
 
+/// Colour of the countries on one side of a comparison, shared by the map and its legend.
+pub fn comparison_color(side: ComparisonSide) -> Color {
+    match side {
+        ComparisonSide::Both => Color::from_rgb(0.2, 0.7, 0.3),
+        ComparisonSide::OnlyFirst => Color::from_rgb(0.2, 0.4, 0.9),
+        ComparisonSide::OnlySecond => Color::from_rgb(0.95, 0.6, 0.1),
+    }
+}
+
+fn lighten(color: Color, amount: f32) -> Color {
+    let mix = |component: f32| component + (1.0 - component) * amount;
+    Color::from_rgb(mix(color.r), mix(color.g), mix(color.b))
+}
+
+/// Longer stays are drawn darker and shorter ones lighter than a plain visit.
+fn with_visit_kind_intensity(color: Color, visit_kind: VisitKind) -> Color {
+    let (factor, towards) = match visit_kind {
+        VisitKind::Lived => (0.6, 0.0),
+        VisitKind::ExtendedStay => (0.8, 0.0),
+        VisitKind::Visited => (1.0, 0.0),
+        VisitKind::DayTrip => (0.65, 1.0),
+        VisitKind::Transit => (0.35, 1.0),
+    };
+    let mix = |component: f32| component * factor + towards * (1.0 - factor);
+    Color::from_rgb(mix(color.r), mix(color.g), mix(color.b))
+}
+
 fn color_from_ascii(ascii: &[u8]) -> Color {
     Color::from_rgb(
         color_component_from_ascii(&ascii[0]),
@@ -119,58 +312,221 @@ fn color_component_from_ascii(ascii: &u8) -> f32 {
     ascii / 26.0
 }
 
-impl<Message> canvas::Program<Message> for WorldMap {
-    type State = ();
+/// A box being dragged on the map to select the countries in it.
+#[derive(Default)]
+struct BoxSelection {
+    start: Option<iced::Point>,
+    end: iced::Point,
+    modifiers: Modifiers,
+}
+
+impl BoxSelection {
+    fn bounds(&self) -> Option<Rectangle> {
+        let start = self.start?;
+        let top_left = iced::Point::new(start.x.min(self.end.x), start.y.min(self.end.y));
+        Some(Rectangle::new(top_left, Size::new((start.x - self.end.x).abs(), (start.y - self.end.y).abs())))
+    }
+}
+
+impl canvas::Program<WorldMapMessage> for WorldMapCanvas<'_> {
+    type State = BoxSelection;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (Status, Option<WorldMapMessage>) {
+        use iced::mouse::{Button, Event as MouseEvent};
+        match event {
+            canvas::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.modifiers = modifiers;
+                (Status::Ignored, None)
+            }
+            canvas::Event::Mouse(MouseEvent::ButtonPressed(Button::Left)) => match cursor.position_in(bounds) {
+                Some(position) => {
+                    state.start = Some(position);
+                    state.end = position;
+                    (Status::Captured, None)
+                }
+                None => (Status::Ignored, None),
+            },
+            canvas::Event::Mouse(MouseEvent::CursorMoved { position }) if state.start.is_some() => {
+                state.end = iced::Point::new(position.x - bounds.x, position.y - bounds.y);
+                (Status::Captured, None)
+            }
+            canvas::Event::Mouse(MouseEvent::ButtonReleased(Button::Left)) => match state.bounds() {
+                Some(selection) => {
+                    state.start = None;
+                    let extend = state.modifiers.command() || state.modifiers.shift();
+                    (Status::Captured, Some(WorldMapMessage::BoxSelected(self.countries_in(bounds.size(), selection), extend)))
+                }
+                None => (Status::Ignored, None),
+            },
+            _ => (Status::Ignored, None),
+        }
+    }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &iced::Renderer,
-        _theme: &iced::Theme,
+        theme: &iced::Theme,
         bounds: Rectangle,
         _cursor: Cursor,
     ) -> Vec<canvas::Geometry> {
-        let country_geom = self.countries_cache.draw(renderer, bounds.size(), |frame| {
-            if let Some(filter) = &self.country_filter {
-                let (selected, unselected): (Vec<&Country>, Vec<&Country>) = self.countries.iter()
-                    .map(|(country, _visited)| country)
-                    .partition(|country| filter.accept(country));
-                for country in unselected {
-                    draw_country(country, CountryRenderStyle::Unselected, frame);
-                }
-                for country in selected {
-                    draw_country(country, CountryRenderStyle::Selected, frame);
+        let map_style = MapStyle::of(theme);
+        let country_geom = self.world_map.countries_cache.draw(renderer, bounds.size(), |frame| {
+            // zoomed in, the countries reach beyond the canvas and would be drawn over the other widgets
+            frame.with_clip(Rectangle::with_size(frame.size()), |frame| self.draw_countries(&map_style, frame));
+        });
+        let mut legend = Frame::new(renderer, bounds.size());
+        draw_legend(&self.legend(&map_style), &map_style, &mut legend);
+        if let Some(selection) = state.bounds() {
+            let path = Path::rectangle(selection.position(), selection.size());
+            legend.fill(&path, Color { a: 0.2, ..map_style.selected_border });
+            legend.stroke(&path, Stroke::default().with_color(map_style.selected_border));
+        }
+        vec![country_geom, legend.into_geometry()]
+    }
+}
+
+impl WorldMapCanvas<'_> {
+    /// ISO2 codes of the countries overlapping a box on a canvas of the given size. A click gives
+    /// an empty box, which selects the country under it.
+    fn countries_in(&self, canvas: Size, selection: Rectangle) -> Vec<String> {
+        let viewport = self.world_map.viewport;
+        let min = viewport.map_point(canvas, selection.position());
+        let max = viewport.map_point(canvas, iced::Point::new(selection.x + selection.width, selection.y + selection.height));
+        self.state.countries()
+            .filter(|country| COUNTRY_POLYGONS.get(&country.iso2)
+                .is_some_and(|polygons| polygons.iter().any(|polygon| polygon.overlaps_box(&min, &max))))
+            .map(|country| country.iso2.clone())
+            .collect()
+    }
+
+    fn draw_countries(&self, map_style: &MapStyle, frame: &mut Frame) {
+        frame.fill_rectangle(iced::Point::ORIGIN, frame.size(), map_style.ocean);
+        if let Some(filter) = &self.world_map.country_filter {
+            let (selected, unselected): (Vec<&Country>, Vec<&Country>) = self.state.countries()
+                .partition(|country| filter.accept(country));
+            for country in unselected {
+                draw_country(country, CountryRenderStyle::Unselected, map_style, self.world_map.viewport, frame);
+            }
+            for country in selected {
+                draw_country(country, CountryRenderStyle::Selected, map_style, self.world_map.viewport, frame);
+            }
+        } else if self.state.compared_with().is_some() {
+            for country in self.state.countries() {
+                draw_country(country, CountryRenderStyle::Compared(self.state.comparison_side(country)), map_style, self.world_map.viewport, frame);
+            }
+        } else {
+            let visit_dates = self.visit_date_range();
+            for (country, visit_kind) in self.state.countries_with_visit_kind(self.world_map.as_of) {
+                draw_country(country, self.scheme_style(country, visit_kind, visit_dates, map_style), map_style, self.world_map.viewport, frame);
+            }
+        }
+    }
+
+    fn scheme_style(&self, country: &Country, visit_kind: Option<VisitKind>, visit_dates: Option<(NaiveDate, NaiveDate)>, map_style: &MapStyle) -> CountryRenderStyle {
+        let colors = self.world_map.map_colors;
+        let Some(visit_kind) = visit_kind else {
+            return match (self.state.wish(country), colors.scheme) {
+                (Some(wish), _) => CountryRenderStyle::Filled(colors.wish(wish.status)),
+                (None, ColorScheme::Classic) => CountryRenderStyle::Normal(None),
+                (None, ColorScheme::Continent) => {
+                    let color = Continent::of_iso2(&country.iso2).map(|continent| colors.continent(continent));
+                    CountryRenderStyle::Filled(lighten(color.unwrap_or(map_style.unvisited), 0.7))
                 }
-            } else {
-                for (country, visited) in &self.countries {
-                    draw_country(country, CountryRenderStyle::Normal(*visited), frame);
+                (None, ColorScheme::Neighbours) => {
+                    CountryRenderStyle::Filled(lighten(colors.graph_color(graph_color(&country.iso2)), 0.7))
                 }
+                (None, _) => CountryRenderStyle::Filled(map_style.unvisited),
+            };
+        };
+        let color = match colors.scheme {
+            ColorScheme::Classic => return CountryRenderStyle::Normal(Some(visit_kind)),
+            ColorScheme::Visited => colors.visited(),
+            ColorScheme::Continent => Continent::of_iso2(&country.iso2)
+                .map(|continent| colors.continent(continent))
+                .unwrap_or(colors.visited()),
+            ColorScheme::Neighbours => colors.graph_color(graph_color(&country.iso2)),
+            ColorScheme::Recency => return CountryRenderStyle::Filled(colors.recency(self.recency(country, visit_dates))),
+        };
+        CountryRenderStyle::Filled(with_visit_kind_intensity(color, visit_kind))
+    }
+
+    /// Oldest and latest visit date of the shown profile.
+    fn visit_date_range(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let dates = self.state.countries().filter_map(|country| self.state.visited_on(country));
+        dates.fold(None, |range, date| match range {
+            None => Some((date, date)),
+            Some((oldest, latest)) => Some((oldest.min(date), latest.max(date))),
+        })
+    }
+
+    /// Position of the visit date of the country between the oldest and the latest one, visits
+    /// with neither a date nor a logged time count as the oldest.
+    fn recency(&self, country: &Country, visit_dates: Option<(NaiveDate, NaiveDate)>) -> f32 {
+        match (self.state.visited_on(country), visit_dates) {
+            (Some(date), Some((oldest, latest))) if latest > oldest => {
+                (date - oldest).num_days() as f32 / (latest - oldest).num_days() as f32
             }
+            (Some(_), _) => 1.0,
+            (None, _) => 0.0,
+        }
+    }
+
+    fn legend(&self, map_style: &MapStyle) -> Vec<(String, Color)> {
+        if self.world_map.country_filter.is_some() {
+            return vec![];
+        }
+        match self.state.compared_with() {
+            Some(compared_with) => ComparisonSide::ALL.into_iter()
+                .map(|side| (side.title(&self.state.profile().name, &compared_with.name), comparison_color(side)))
+                .collect(),
+            None => self.world_map.map_colors.legend(map_style.unvisited),
+        }
+    }
+}
+
+const LEGEND_LINE_HEIGHT: f32 = 18.0;
+
+fn draw_legend(entries: &[(String, Color)], map_style: &MapStyle, frame: &mut Frame) {
+    if entries.is_empty() {
+        return;
+    }
+    let height = entries.len() as f32 * LEGEND_LINE_HEIGHT + 10.0;
+    let top = frame.height() - height - 10.0;
+    frame.fill_rectangle(
+        iced::Point::new(10.0, top),
+        iced::Size::new(150.0, height),
+        map_style.legend_background,
+    );
+    for (index, (label, color)) in entries.iter().enumerate() {
+        let y = top + 5.0 + index as f32 * LEGEND_LINE_HEIGHT;
+        frame.fill_rectangle(iced::Point::new(15.0, y + 2.0), iced::Size::new(12.0, 12.0), *color);
+        frame.fill_text(Text {
+            content: label.clone(),
+            position: iced::Point::new(33.0, y),
+            size: 14.0,
+            color: map_style.legend_text,
+            ..Text::default()
         });
-        vec![country_geom]
     }
 }
 
-fn draw_country(country: &Country, style: CountryRenderStyle, frame: &mut Frame) {
+fn draw_country(country: &Country, style: CountryRenderStyle, map_style: &MapStyle, viewport: Viewport, frame: &mut Frame) {
     if let Some(polygons) = COUNTRY_POLYGONS.get(&country.iso2) {
         for polygon in polygons {
-            draw_polygon(polygon, country, &style, frame);
+            draw_polygon(polygon, country, &style, map_style, viewport, frame);
         }
     }
 }
 
-fn draw_polygon(polygon: &Polygon, country: &Country, style: &CountryRenderStyle, frame: &mut Frame) {
-    let aspect_ratio_svg = SVG_WIDTH / SVG_HEIGT;
-    let aspect_ratio_frame = frame.width() / frame.height();
-    let map_render_size: Point;
-    let map_render_offset: Point;
-    if aspect_ratio_svg > aspect_ratio_frame {
-        map_render_size = Point(frame.width(), frame.width() / aspect_ratio_svg);
-        map_render_offset = Point(0.0, (frame.height() - map_render_size.1) / 2.0);
-    } else {
-        map_render_size = Point(frame.height() * aspect_ratio_svg, frame.height());
-        map_render_offset = Point((frame.width() - map_render_size.0) / 2.0, 0.0);
-    }
+fn draw_polygon(polygon: &Polygon, country: &Country, style: &CountryRenderStyle, map_style: &MapStyle, viewport: Viewport, frame: &mut Frame) {
+    let (map_render_size, map_render_offset) = viewport.layout(frame.size());
     if polygon.0.is_empty() {
         return;
     }
@@ -187,10 +543,10 @@ fn draw_polygon(polygon: &Polygon, country: &Country, style: &CountryRenderStyle
         }
         path.close();
     });
-    let color = style.get_fill_color(country);
+    let color = style.get_fill_color(country, map_style);
     frame.fill(&path, Fill { style: fill::Style::Solid(color), ..Fill::default() });
     frame.stroke(&path, Stroke {
-        style: style.get_stroke_style(),
+        style: style.get_stroke_style(map_style),
         width: 1.0,
         ..Stroke::default()
     });